@@ -1,9 +1,11 @@
 //! JWT sign/verify for Đấu Trường Vui auth
 //!
-//! HS256, JWT_SECRET env, 30-day expiry.
+//! HS256, JWT_SECRET env, 30-day expiry (per-role overrides via JWT_ROLE_TTLS).
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "auth")]
+use std::collections::HashMap;
 use std::env;
 
 #[cfg(feature = "auth")]
@@ -32,19 +34,69 @@ fn get_secret() -> String {
     env::var("JWT_SECRET").unwrap_or_else(|_| "aivaAPI".to_string())
 }
 
-/// Sign a JWT token for the given user
+/// Parse `JWT_ROLE_TTLS` (e.g. `admin=3600,service=31536000`, in seconds).
+/// Roles match exactly, as in `MCP_TOOL_ROLES`; a malformed entry is an error.
 #[cfg(feature = "auth")]
-pub fn sign_jwt(user_id: &str, email: &str, role: &str) -> Result<String> {
-    let secret = get_secret();
-    let now = chrono::Utc::now().timestamp() as u64;
+fn parse_role_ttls(spec: &str) -> Result<HashMap<String, u64>> {
+    let mut ttls = HashMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let ttl = entry.split_once('=').and_then(|(role, secs)| {
+            let role = role.trim();
+            let secs = secs.trim().parse::<u64>().ok().filter(|secs| *secs > 0)?;
+            (!role.is_empty()).then(|| (role.to_string(), secs))
+        });
+        match ttl {
+            Some((role, secs)) => {
+                ttls.insert(role, secs);
+            }
+            None => anyhow::bail!("Invalid JWT_ROLE_TTLS entry '{entry}': expected role=seconds"),
+        }
+    }
+    Ok(ttls)
+}
+
+/// Per-role token lifetimes from `JWT_ROLE_TTLS`; unset means every role
+/// gets the 30-day default
+#[cfg(feature = "auth")]
+pub fn role_ttls_from_env() -> Result<HashMap<String, u64>> {
+    match env::var("JWT_ROLE_TTLS") {
+        Ok(spec) => parse_role_ttls(&spec),
+        Err(_) => Ok(HashMap::new()),
+    }
+}
+
+/// Token lifetime for `role`, else the 30-day default
+#[cfg(feature = "auth")]
+fn expiry_secs(role: &str, role_ttls: &HashMap<String, u64>) -> u64 {
+    role_ttls.get(role).copied().unwrap_or(DEFAULT_EXPIRY_SECS)
+}
 
-    let claims = Claims {
+/// Claims for a new token issued at `now`
+#[cfg(feature = "auth")]
+fn new_claims(
+    user_id: &str,
+    email: &str,
+    role: &str,
+    now: u64,
+    role_ttls: &HashMap<String, u64>,
+) -> Claims {
+    Claims {
         sub: user_id.to_string(),
         email: email.to_string(),
         role: role.to_string(),
         iat: now,
-        exp: now + DEFAULT_EXPIRY_SECS,
-    };
+        exp: now + expiry_secs(role, role_ttls),
+    }
+}
+
+/// Sign a JWT token for the given user
+#[cfg(feature = "auth")]
+pub fn sign_jwt(user_id: &str, email: &str, role: &str) -> Result<String> {
+    let secret = get_secret();
+    let now = chrono::Utc::now().timestamp() as u64;
+    let role_ttls = role_ttls_from_env()?;
+
+    let claims = new_claims(user_id, email, role, now, &role_ttls);
 
     let token = encode(
         &Header::default(),
@@ -187,6 +239,37 @@ mod tests {
         assert_eq!(claims.role, "admin");
     }
 
+    #[test]
+    #[cfg(feature = "auth")]
+    fn test_role_ttl_overrides() {
+        let ttls = parse_role_ttls("admin=3600, service=31536000").unwrap();
+        let now = 1_700_000_000;
+
+        let admin = new_claims("a", "admin@netadx.ai", "admin", now, &ttls);
+        let service = new_claims("s", "svc@netadx.ai", "service", now, &ttls);
+        let user = new_claims("u", "user@netadx.ai", "user", now, &ttls);
+
+        assert_eq!(admin.exp, now + 3600);
+        assert_eq!(service.exp, now + 31_536_000);
+        assert_eq!(user.exp, now + DEFAULT_EXPIRY_SECS);
+    }
+
+    #[test]
+    #[cfg(feature = "auth")]
+    fn test_role_ttl_invalid_entries_rejected() {
+        assert_eq!(expiry_secs("admin", &HashMap::new()), DEFAULT_EXPIRY_SECS);
+        assert!(parse_role_ttls("").unwrap().is_empty());
+        for spec in ["admin=soon", "admin=0", "admin", "=60", "admin=60,user=-1"] {
+            let err = parse_role_ttls(spec).unwrap_err();
+            assert!(err.to_string().contains("JWT_ROLE_TTLS"), "{spec}");
+        }
+
+        // Roles match exactly, like MCP_TOOL_ROLES
+        let ttls = parse_role_ttls("admin=60").unwrap();
+        assert_eq!(expiry_secs("admin", &ttls), 60);
+        assert_eq!(expiry_secs("Admin", &ttls), DEFAULT_EXPIRY_SECS);
+    }
+
     #[test]
     #[cfg(feature = "auth")]
     #[ignore] // Races with parallel tests that mutate JWT_SECRET env var. Run: cargo test test_sign_jwt_uses_env -- --test-threads=1 --ignored
@@ -18,10 +18,10 @@ mod credits;
 mod upload;
 
 use mcp::McpServer;
-use utils::Logger;
+use utils::{LogFormat, Logger};
 
 #[cfg(feature = "http-stream")]
-use mcp::run_http_stream_server;
+use mcp::{run_http_stream_server, HttpServerConfig};
 
 #[cfg(feature = "http-stream")]
 use tracing::info;
@@ -43,13 +43,25 @@ struct Args {
     #[arg(short, long, help = "Enable verbose logging")]
     verbose: bool,
 
+    #[arg(long, value_enum, default_value = "text", help = "Log output format")]
+    log_format: LogFormat,
+
     #[arg(
         short,
         long,
-        help = "Bind address for HTTP server",
-        default_value = "127.0.0.1:8030"
+        help = "Bind address for HTTP server [default: 127.0.0.1:8030, env: MCP_HTTP_ADDR]"
     )]
-    bind: String,
+    bind: Option<String>,
+
+    #[arg(long, help = "Prefix for MCP HTTP routes (e.g. /mcp)")]
+    route_prefix: Option<String>,
+
+    #[arg(long, help = "TOML file with HTTP server settings (replaces MCP_HTTP_* env)")]
+    http_config: Option<std::path::PathBuf>,
+
+    #[cfg(feature = "postgres")]
+    #[arg(long, help = "Maximum rows a db query may return (overrides DB_MAX_ROWS)")]
+    db_max_rows: Option<u64>,
 }
 
 #[tokio::main]
@@ -58,6 +70,13 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
 
+    #[cfg(feature = "postgres")]
+    tools::db::init_config(args.db_max_rows).map_err(anyhow::Error::msg)?;
+
+    // Report a malformed JWT_ROLE_TTLS now rather than at the first login
+    #[cfg(feature = "auth")]
+    auth::jwt::role_ttls_from_env()?;
+
     let result = match args.mode {
         ServerMode::Stdio => {
             if args.verbose {
@@ -65,7 +84,7 @@ async fn main() -> Result<()> {
             } else {
                 std::env::set_var("RUST_LOG", "off");
             }
-            Logger::init();
+            Logger::init(args.log_format);
             run_stdio_server().await
         }
         #[cfg(feature = "http-stream")]
@@ -75,10 +94,13 @@ async fn main() -> Result<()> {
             } else if std::env::var("RUST_LOG").is_err() {
                 std::env::set_var("RUST_LOG", "info");
             }
-            Logger::init();
+            Logger::init(args.log_format);
             info!("mcp-dautruongvui-be v{}", env!("CARGO_PKG_VERSION"));
             info!("Starting MCP server in HTTP Streaming mode");
-            run_http_stream_server(&args.bind).await
+            match http_server_config(&args) {
+                Ok(config) => run_http_stream_server(config).await,
+                Err(e) => Err(e),
+            }
         }
     };
 
@@ -90,4 +112,21 @@ async fn run_stdio_server() -> Result<()> {
     let server = McpServer::new();
     server.run().await?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Resolve HTTP settings: TOML file or `MCP_HTTP_*` env, then CLI overrides
+#[cfg(feature = "http-stream")]
+fn http_server_config(args: &Args) -> Result<HttpServerConfig> {
+    let mut config = match &args.http_config {
+        Some(path) => HttpServerConfig::from_toml_file(path)?,
+        None => HttpServerConfig::from_env()?,
+    };
+    if let Some(bind) = &args.bind {
+        config = config.with_addr_str(bind)?;
+    }
+    if let Some(prefix) = &args.route_prefix {
+        config = config.with_route_prefix(prefix);
+    }
+    config.validate()?;
+    Ok(config)
+}
@@ -0,0 +1,119 @@
+//! Token-protected admin endpoints for the HTTP server
+//!
+//! Only mounted when an admin token is configured.
+
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Admin shutdown handler - checks the bearer token, then asks the server to
+/// stop accepting connections and drain. Responds 202 before draining.
+pub async fn admin_shutdown_handler(
+    shutdown: Arc<Notify>,
+    headers: HeaderMap,
+    token: Arc<str>,
+) -> Response {
+    let supplied = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("");
+
+    if !constant_time_eq(supplied.as_bytes(), token.as_bytes()) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({
+                "success": false,
+                "error": "Invalid admin token"
+            })),
+        )
+            .into_response();
+    }
+
+    shutdown.notify_one();
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "success": true,
+            "status": "shutting_down"
+        })),
+    )
+        .into_response()
+}
+
+/// Compare secrets without exiting early on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::http_config::HttpServerConfig;
+    use crate::mcp::http_serve::{serve_until, ConnectionLimits};
+    use crate::mcp::http_stream_server::{build_router, AppState};
+    use crate::mcp::protocol_handler::ProtocolHandler;
+    use axum::{body::Body, http::Request};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"s3cret", b"s3cret"));
+        assert!(!constant_time_eq(b"s3cret", b"s3creT"));
+        assert!(!constant_time_eq(b"s3cret", b"s3cret!"));
+    }
+
+    #[tokio::test]
+    async fn test_admin_shutdown_absent_without_token() {
+        let app = build_router(
+            AppState::new(Arc::new(ProtocolHandler::new())),
+            &HttpServerConfig::default(),
+        );
+        let request = Request::builder()
+            .method("POST")
+            .uri("/admin/shutdown")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_admin_shutdown_stops_server() {
+        let config = HttpServerConfig {
+            admin_token: Some("s3cret".to_string()),
+            ..HttpServerConfig::default()
+        };
+        let state = AppState::new(Arc::new(ProtocolHandler::new()));
+        let shutdown = state.shutdown.clone();
+        let app = build_router(state, &config);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let limits = ConnectionLimits::default();
+        let server = tokio::spawn(serve_until(listener, app, limits, async move {
+            shutdown.notified().await
+        }));
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{addr}/admin/shutdown");
+
+        let response = client.post(&url).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let response = client.post(&url).bearer_auth("s3cret").send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server did not stop")
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+//! Tool-level authorization
+//!
+//! Maps tool names to the role a caller needs to invoke them. Anonymous
+//! callers are refused gated tools; `admin` callers may invoke every tool.
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+
+/// Role that bypasses every tool requirement
+pub const ADMIN_ROLE: &str = "admin";
+
+/// Authenticated caller of a tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub user_id: String,
+    pub role: String,
+}
+
+impl Principal {
+    pub fn is_admin(&self) -> bool {
+        self.role == ADMIN_ROLE
+    }
+}
+
+#[cfg(feature = "auth")]
+impl From<crate::auth::jwt::Claims> for Principal {
+    fn from(claims: crate::auth::jwt::Claims) -> Self {
+        Self {
+            user_id: claims.sub,
+            role: claims.role,
+        }
+    }
+}
+
+/// Caller lacks the role required by a tool
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Insufficient permissions: tool {tool} requires role {required_role}")]
+pub struct InsufficientPermissions {
+    pub tool: String,
+    pub required_role: String,
+}
+
+/// Tool name → required role. Tools without an entry are open to everyone.
+#[derive(Debug, Clone, Default)]
+pub struct ToolPermissions {
+    required: HashMap<String, String>,
+}
+
+impl ToolPermissions {
+    /// Require `role` to call `tool`
+    pub fn require(mut self, tool: impl Into<String>, role: impl Into<String>) -> Self {
+        self.required.insert(tool.into(), role.into());
+        self
+    }
+
+    /// Build from `MCP_TOOL_ROLES`, e.g. `db=admin,upload=user`.
+    /// Unset or empty means no tool is gated.
+    #[cfg_attr(not(feature = "http-stream"), allow(dead_code))]
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("MCP_TOOL_ROLES") {
+            Ok(spec) => Self::parse(&spec),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    #[cfg_attr(not(feature = "http-stream"), allow(dead_code))]
+    fn parse(spec: &str) -> Result<Self> {
+        let mut permissions = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (tool, role) = match entry.split_once('=') {
+                Some((tool, role)) if !tool.trim().is_empty() && !role.trim().is_empty() => {
+                    (tool.trim(), role.trim())
+                }
+                _ => bail!("Invalid MCP_TOOL_ROLES entry '{entry}': expected tool=role"),
+            };
+            permissions = permissions.require(tool, role);
+        }
+        Ok(permissions)
+    }
+
+    /// Check whether `principal` may call `tool`. Without a principal, only
+    /// tools that require no role are allowed.
+    pub fn check(
+        &self,
+        tool: &str,
+        principal: Option<&Principal>,
+    ) -> Result<(), InsufficientPermissions> {
+        let Some(role) = self.required.get(tool) else {
+            return Ok(());
+        };
+        match principal {
+            Some(principal) if principal.role == *role || principal.is_admin() => Ok(()),
+            _ => Err(InsufficientPermissions {
+                tool: tool.to_string(),
+                required_role: role.clone(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(role: &str) -> Principal {
+        Principal {
+            user_id: "uuid-1".to_string(),
+            role: role.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let permissions = ToolPermissions::default().require("db", "editor");
+
+        assert!(permissions.check("db", Some(&principal("editor"))).is_ok());
+        assert!(permissions.check("db", Some(&principal(ADMIN_ROLE))).is_ok());
+        assert!(permissions.check("auth", Some(&principal("user"))).is_ok());
+        assert!(permissions.check("auth", None).is_ok());
+
+        let err = permissions.check("db", Some(&principal("user"))).unwrap_err();
+        assert_eq!(err.required_role, "editor");
+
+        // Anonymous callers never pass a role requirement
+        assert!(permissions.check("db", None).is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let permissions = ToolPermissions::parse(" db=admin, upload = user ,").unwrap();
+        assert!(permissions.check("db", Some(&principal("user"))).is_err());
+        assert!(permissions.check("upload", Some(&principal("user"))).is_ok());
+
+        assert!(ToolPermissions::parse("").unwrap().required.is_empty());
+        assert!(ToolPermissions::parse("db").is_err());
+        assert!(ToolPermissions::parse("db=").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Request deadlines for HTTP tool calls
+//!
+//! Every request gets a deadline from the configured `timeout_ms`, which the
+//! client may shorten with `X-Timeout-Ms`. Tool calls are cancelled when it
+//! passes and answered with 504.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Client-supplied deadline in milliseconds; can only shorten `timeout_ms`
+pub const TIMEOUT_HEADER: &str = "x-timeout-ms";
+
+/// Instant by which a request's tool call must finish
+#[derive(Debug, Clone, Copy)]
+pub struct RequestDeadline(pub tokio::time::Instant);
+
+/// Effective timeout: the `X-Timeout-Ms` header or the configured default,
+/// whichever is shorter. Invalid or zero header values are ignored.
+pub fn request_timeout(headers: &HeaderMap, default: Option<Duration>) -> Option<Duration> {
+    let requested = headers
+        .get(TIMEOUT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&ms| ms > 0)
+        .map(Duration::from_millis);
+
+    match (requested, default) {
+        (Some(requested), Some(default)) => Some(requested.min(default)),
+        (requested, default) => requested.or(default),
+    }
+}
+
+/// Middleware attaching a [`RequestDeadline`] to requests that have one
+pub async fn stamp_deadline(
+    State(default): State<Option<Duration>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    if let Some(timeout) = request_timeout(request.headers(), default) {
+        let deadline = tokio::time::Instant::now() + timeout;
+        request.extensions_mut().insert(RequestDeadline(deadline));
+    }
+    next.run(request).await
+}
+
+/// 504 for a tool call cut off by its deadline, 200 otherwise
+pub fn tool_call_status(response: &Value) -> StatusCode {
+    if response["error"]["data"]["kind"] == "deadline_exceeded" {
+        StatusCode::GATEWAY_TIMEOUT
+    } else {
+        StatusCode::OK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_timeout() {
+        let default = Some(Duration::from_secs(30));
+        let with_header = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(TIMEOUT_HEADER, value.parse().unwrap());
+            headers
+        };
+
+        assert_eq!(request_timeout(&HeaderMap::new(), default), default);
+        assert_eq!(
+            request_timeout(&with_header("250"), default),
+            Some(Duration::from_millis(250))
+        );
+        // The client can only shorten the configured timeout
+        assert_eq!(request_timeout(&with_header("60000"), default), default);
+        assert_eq!(request_timeout(&with_header("0"), None), None);
+        assert_eq!(request_timeout(&with_header("soon"), None), None);
+        assert_eq!(
+            request_timeout(&with_header("100"), None),
+            Some(Duration::from_millis(100))
+        );
+    }
+
+    #[test]
+    fn test_tool_call_status() {
+        let timed_out = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {
+                "code": -32001,
+                "message": "Tool db did not finish before the request deadline",
+                "data": { "kind": "deadline_exceeded", "tool": "db" }
+            }
+        });
+        assert_eq!(tool_call_status(&timed_out), StatusCode::GATEWAY_TIMEOUT);
+
+        let failed = json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32603 } });
+        assert_eq!(tool_call_status(&failed), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_header_cuts_off_slow_tool() {
+        use crate::mcp::http_config::HttpServerConfig;
+        use crate::mcp::http_stream_server::{build_router, AppState};
+        use crate::mcp::protocol_handler::ProtocolHandler;
+        use crate::mcp::tool_set::testing::StubTools;
+        use axum::http::header;
+        use std::sync::Arc;
+        use tower::ServiceExt;
+
+        let handler = ProtocolHandler::new().with_tools(Arc::new(StubTools));
+        let app = build_router(AppState::new(Arc::new(handler)), &HttpServerConfig::default());
+        let request = Request::builder()
+            .method("POST")
+            .uri("/tools/call")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(TIMEOUT_HEADER, "50")
+            .body(Body::from(
+                json!({ "name": "slow", "arguments": { "ms": 5000 } }).to_string(),
+            ))
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], -32001);
+        assert_eq!(body["error"]["data"]["tool"], "slow");
+    }
+}
@@ -0,0 +1,448 @@
+//! HTTP server configuration
+//!
+//! Built from defaults, environment variables (`MCP_HTTP_*`) or a TOML file.
+//! Invalid values are reported as errors instead of panicking at startup.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Default bind address for HTTP mode
+pub const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1:8030";
+
+/// Default request body limit (matches axum's built-in 2 MB limit)
+pub const DEFAULT_MAX_REQUEST_SIZE: usize = 2 * 1024 * 1024;
+
+/// Root paths served outside the route prefix, which it must not shadow
+const RESERVED_PREFIXES: [&str; 4] = ["health", "credits", "upload", "admin"];
+
+/// Default time a client gets to send request headers
+pub const DEFAULT_HEADER_READ_TIMEOUT_MS: u64 = 30_000;
+
+/// HTTP server configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpServerConfig {
+    /// `host:port` to listen on
+    pub bind_address: String,
+    /// Prefix for MCP routes, normalized to `/segment` or empty for root
+    pub route_prefix: String,
+    /// Per-request timeout in milliseconds (no timeout when unset)
+    pub timeout_ms: Option<u64>,
+    /// Open connections at once; surplus connections get 503 and are closed
+    /// (unbounded when unset)
+    pub max_connections: Option<usize>,
+    /// Close keep-alive connections with no request for this many
+    /// milliseconds (kept open when unset)
+    pub idle_timeout_ms: Option<u64>,
+    /// Close connections that have not sent full request headers within this
+    /// many milliseconds
+    pub header_read_timeout_ms: Option<u64>,
+    /// Sustained requests per second per client IP (no limit when unset)
+    pub rate_limit_per_second: Option<u32>,
+    /// Requests a client may burst above the sustained rate
+    /// (defaults to `rate_limit_per_second`)
+    pub rate_limit_burst: Option<u32>,
+    /// Maximum request body size in bytes
+    pub max_request_size: usize,
+    /// Enable the permissive CORS layer
+    pub cors: bool,
+    /// Enable per-request trace logging
+    pub logging: bool,
+    /// Bearer token for `POST /admin/shutdown`; the route is absent when unset
+    pub admin_token: Option<String>,
+    /// Serve an OpenAPI document at `/openapi.json`
+    pub enable_openapi: bool,
+}
+
+impl Default for HttpServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: DEFAULT_BIND_ADDRESS.to_string(),
+            route_prefix: String::new(),
+            timeout_ms: None,
+            max_connections: None,
+            idle_timeout_ms: None,
+            header_read_timeout_ms: Some(DEFAULT_HEADER_READ_TIMEOUT_MS),
+            rate_limit_per_second: None,
+            rate_limit_burst: None,
+            max_request_size: DEFAULT_MAX_REQUEST_SIZE,
+            cors: true,
+            logging: true,
+            admin_token: None,
+            enable_openapi: false,
+        }
+    }
+}
+
+impl HttpServerConfig {
+    /// Build from environment variables, falling back to defaults:
+    /// `MCP_HTTP_ADDR`, `MCP_HTTP_ROUTE_PREFIX`, `MCP_HTTP_TIMEOUT_MS`,
+    /// `MCP_HTTP_MAX_CONNECTIONS`, `MCP_HTTP_IDLE_TIMEOUT_MS`,
+    /// `MCP_HTTP_HEADER_READ_TIMEOUT_MS`, `MCP_HTTP_RATE_LIMIT_PER_SECOND`,
+    /// `MCP_HTTP_RATE_LIMIT_BURST`, `MCP_HTTP_MAX_REQUEST_SIZE`, `MCP_HTTP_CORS`,
+    /// `MCP_HTTP_LOGGING`, `MCP_HTTP_ADMIN_TOKEN`, `MCP_HTTP_OPENAPI`.
+    pub fn from_env() -> Result<Self> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Same as [`Self::from_env`] with an injectable variable lookup
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut config = Self::default();
+
+        if let Some(addr) = lookup("MCP_HTTP_ADDR") {
+            config.bind_address = addr;
+        }
+        if let Some(prefix) = lookup("MCP_HTTP_ROUTE_PREFIX") {
+            config.route_prefix = prefix;
+        }
+        if let Some(timeout) = lookup("MCP_HTTP_TIMEOUT_MS") {
+            config.timeout_ms = Some(
+                timeout
+                    .parse()
+                    .with_context(|| format!("Invalid MCP_HTTP_TIMEOUT_MS: {timeout}"))?,
+            );
+        }
+        if let Some(max) = lookup("MCP_HTTP_MAX_CONNECTIONS") {
+            config.max_connections = Some(
+                max.parse()
+                    .with_context(|| format!("Invalid MCP_HTTP_MAX_CONNECTIONS: {max}"))?,
+            );
+        }
+        if let Some(idle) = lookup("MCP_HTTP_IDLE_TIMEOUT_MS") {
+            config.idle_timeout_ms = Some(
+                idle.parse()
+                    .with_context(|| format!("Invalid MCP_HTTP_IDLE_TIMEOUT_MS: {idle}"))?,
+            );
+        }
+        if let Some(header) = lookup("MCP_HTTP_HEADER_READ_TIMEOUT_MS") {
+            config.header_read_timeout_ms = Some(header.parse().with_context(|| {
+                format!("Invalid MCP_HTTP_HEADER_READ_TIMEOUT_MS: {header}")
+            })?);
+        }
+        if let Some(rate) = lookup("MCP_HTTP_RATE_LIMIT_PER_SECOND") {
+            config.rate_limit_per_second = Some(
+                rate.parse()
+                    .with_context(|| format!("Invalid MCP_HTTP_RATE_LIMIT_PER_SECOND: {rate}"))?,
+            );
+        }
+        if let Some(burst) = lookup("MCP_HTTP_RATE_LIMIT_BURST") {
+            config.rate_limit_burst = Some(
+                burst
+                    .parse()
+                    .with_context(|| format!("Invalid MCP_HTTP_RATE_LIMIT_BURST: {burst}"))?,
+            );
+        }
+        if let Some(size) = lookup("MCP_HTTP_MAX_REQUEST_SIZE") {
+            config.max_request_size = size
+                .parse()
+                .with_context(|| format!("Invalid MCP_HTTP_MAX_REQUEST_SIZE: {size}"))?;
+        }
+        if let Some(cors) = lookup("MCP_HTTP_CORS") {
+            config.cors = parse_bool("MCP_HTTP_CORS", &cors)?;
+        }
+        if let Some(logging) = lookup("MCP_HTTP_LOGGING") {
+            config.logging = parse_bool("MCP_HTTP_LOGGING", &logging)?;
+        }
+        if let Some(token) = lookup("MCP_HTTP_ADMIN_TOKEN") {
+            config.admin_token = Some(token);
+        }
+        if let Some(openapi) = lookup("MCP_HTTP_OPENAPI") {
+            config.enable_openapi = parse_bool("MCP_HTTP_OPENAPI", &openapi)?;
+        }
+
+        config.route_prefix = normalize_route_prefix(&config.route_prefix);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load from a TOML file; missing keys take their default values
+    pub fn from_toml_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read HTTP config {}", path.display()))?;
+        Self::from_toml_str(&content)
+            .with_context(|| format!("Invalid HTTP config {}", path.display()))
+    }
+
+    fn from_toml_str(content: &str) -> Result<Self> {
+        let mut config: Self = toml::from_str(content)?;
+        config.route_prefix = normalize_route_prefix(&config.route_prefix);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Set the bind address from a user-supplied `host:port` string,
+    /// returning an error instead of accepting an unusable address
+    pub fn with_addr_str(mut self, addr: &str) -> Result<Self> {
+        let addr = addr.trim();
+        validate_bind_address(addr)?;
+        self.bind_address = addr.to_string();
+        Ok(self)
+    }
+
+    /// Mount MCP routes (`/`, `/rpc`, `/tools`, `/tools/call`) under a prefix,
+    /// e.g. `mcp` or `/mcp/` both become `/mcp`.
+    pub fn with_route_prefix(mut self, prefix: impl AsRef<str>) -> Self {
+        self.route_prefix = normalize_route_prefix(prefix.as_ref());
+        self
+    }
+
+    /// Check that the values can actually be served
+    pub fn validate(&self) -> Result<()> {
+        validate_bind_address(&self.bind_address)?;
+        validate_route_prefix(&self.route_prefix)?;
+        if self.max_request_size == 0 {
+            bail!("max_request_size must be greater than 0");
+        }
+        if self.timeout_ms == Some(0) {
+            bail!("timeout_ms must be greater than 0 when set");
+        }
+        if self.max_connections == Some(0) {
+            bail!("max_connections must be greater than 0 when set");
+        }
+        if self.idle_timeout_ms == Some(0) || self.header_read_timeout_ms == Some(0) {
+            bail!("connection timeouts must be greater than 0 when set");
+        }
+        if self.rate_limit_per_second == Some(0) || self.rate_limit_burst == Some(0) {
+            bail!("rate limit values must be greater than 0 when set");
+        }
+        if self.rate_limit_burst.is_some() && self.rate_limit_per_second.is_none() {
+            bail!("rate_limit_burst requires rate_limit_per_second");
+        }
+        if self.admin_token.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            bail!("admin_token must not be empty when set");
+        }
+        Ok(())
+    }
+}
+
+/// Validate a `host:port` bind address without resolving the host
+fn validate_bind_address(addr: &str) -> Result<()> {
+    let (host, port) = addr
+        .rsplit_once(':')
+        .with_context(|| format!("Invalid bind address '{addr}': expected host:port"))?;
+    if host.is_empty() {
+        bail!("Invalid bind address '{addr}': missing host");
+    }
+    let port = port
+        .parse::<u16>()
+        .with_context(|| format!("Invalid bind address '{addr}': bad port '{port}'"))?;
+    if port == 0 {
+        bail!("Invalid bind address '{addr}': port 0 is not allowed, choose an explicit port");
+    }
+    Ok(())
+}
+
+/// Check a normalized route prefix: plain `[A-Za-z0-9_-]` segments (axum
+/// treats `:`, `*` and `{` as captures) not starting with a reserved path
+fn validate_route_prefix(prefix: &str) -> Result<()> {
+    let Some(path) = prefix.strip_prefix('/') else {
+        return Ok(());
+    };
+    let plain = |segment: &str| {
+        !segment.is_empty()
+            && segment
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+    };
+    if !path.split('/').all(plain) {
+        bail!("Invalid route prefix '{prefix}': use letters, digits, '_' and '-' segments");
+    }
+    let first = path.split('/').next().unwrap_or_default();
+    if RESERVED_PREFIXES.contains(&first) {
+        bail!("Invalid route prefix '{prefix}': /{first} is served at the root");
+    }
+    Ok(())
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => bail!("Invalid {key}: expected true/false, got '{value}'"),
+    }
+}
+
+/// Normalize a route prefix to a leading slash and no trailing slash.
+/// Empty or `/` means no prefix.
+fn normalize_route_prefix(prefix: &str) -> String {
+    let trimmed = prefix.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("/{trimmed}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = HttpServerConfig::from_lookup(lookup(&[])).unwrap();
+        assert_eq!(config.bind_address, DEFAULT_BIND_ADDRESS);
+        assert_eq!(config.route_prefix, "");
+        assert_eq!(config.timeout_ms, None);
+        assert_eq!(config.idle_timeout_ms, None);
+        assert_eq!(
+            config.header_read_timeout_ms,
+            Some(DEFAULT_HEADER_READ_TIMEOUT_MS)
+        );
+        assert_eq!(config.max_request_size, DEFAULT_MAX_REQUEST_SIZE);
+        assert!(config.cors);
+        assert!(config.logging);
+        assert!(!config.enable_openapi);
+    }
+
+    #[test]
+    fn test_from_env_values() {
+        let config = HttpServerConfig::from_lookup(lookup(&[
+            ("MCP_HTTP_ADDR", "0.0.0.0:9000"),
+            ("MCP_HTTP_ROUTE_PREFIX", "mcp/"),
+            ("MCP_HTTP_TIMEOUT_MS", "5000"),
+            ("MCP_HTTP_MAX_CONNECTIONS", "64"),
+            ("MCP_HTTP_IDLE_TIMEOUT_MS", "60000"),
+            ("MCP_HTTP_HEADER_READ_TIMEOUT_MS", "5000"),
+            ("MCP_HTTP_RATE_LIMIT_PER_SECOND", "10"),
+            ("MCP_HTTP_RATE_LIMIT_BURST", "20"),
+            ("MCP_HTTP_MAX_REQUEST_SIZE", "1048576"),
+            ("MCP_HTTP_CORS", "false"),
+            ("MCP_HTTP_LOGGING", "off"),
+            ("MCP_HTTP_ADMIN_TOKEN", "s3cret"),
+            ("MCP_HTTP_OPENAPI", "true"),
+        ]))
+        .unwrap();
+        assert_eq!(config.bind_address, "0.0.0.0:9000");
+        assert_eq!(config.route_prefix, "/mcp");
+        assert_eq!(config.timeout_ms, Some(5000));
+        assert_eq!(config.max_connections, Some(64));
+        assert_eq!(config.idle_timeout_ms, Some(60000));
+        assert_eq!(config.header_read_timeout_ms, Some(5000));
+        assert_eq!(config.rate_limit_per_second, Some(10));
+        assert_eq!(config.rate_limit_burst, Some(20));
+        assert_eq!(config.max_request_size, 1_048_576);
+        assert!(!config.cors);
+        assert!(!config.logging);
+        assert_eq!(config.admin_token.as_deref(), Some("s3cret"));
+        assert!(config.enable_openapi);
+    }
+
+    #[test]
+    fn test_from_env_invalid_address() {
+        let err = HttpServerConfig::from_lookup(lookup(&[("MCP_HTTP_ADDR", "not-an-address")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid bind address"));
+
+        let err = HttpServerConfig::from_lookup(lookup(&[("MCP_HTTP_ADDR", "localhost:99999")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("bad port"));
+    }
+
+    #[test]
+    fn test_from_env_invalid_numbers_and_bools() {
+        assert!(HttpServerConfig::from_lookup(lookup(&[("MCP_HTTP_TIMEOUT_MS", "soon")])).is_err());
+        assert!(HttpServerConfig::from_lookup(lookup(&[("MCP_HTTP_CORS", "maybe")])).is_err());
+        assert!(
+            HttpServerConfig::from_lookup(lookup(&[("MCP_HTTP_MAX_CONNECTIONS", "0")])).is_err()
+        );
+        assert!(
+            HttpServerConfig::from_lookup(lookup(&[("MCP_HTTP_IDLE_TIMEOUT_MS", "0")])).is_err()
+        );
+        assert!(HttpServerConfig::from_lookup(lookup(&[(
+            "MCP_HTTP_HEADER_READ_TIMEOUT_MS",
+            "soon"
+        )]))
+        .is_err());
+        assert!(
+            HttpServerConfig::from_lookup(lookup(&[("MCP_HTTP_RATE_LIMIT_BURST", "5")])).is_err()
+        );
+        assert!(HttpServerConfig::from_lookup(lookup(&[("MCP_HTTP_ADMIN_TOKEN", " ")])).is_err());
+        assert!(
+            HttpServerConfig::from_lookup(lookup(&[("MCP_HTTP_MAX_REQUEST_SIZE", "0")])).is_err()
+        );
+    }
+
+    #[test]
+    fn test_with_addr_str() {
+        let config = HttpServerConfig::default()
+            .with_addr_str(" 0.0.0.0:8080 ")
+            .unwrap();
+        assert_eq!(config.bind_address, "0.0.0.0:8080");
+
+        let config = HttpServerConfig::default()
+            .with_addr_str("[::1]:8030")
+            .unwrap();
+        assert_eq!(config.bind_address, "[::1]:8030");
+
+        assert!(HttpServerConfig::default().with_addr_str("8080").is_err());
+        assert!(HttpServerConfig::default().with_addr_str(":8080").is_err());
+        assert!(HttpServerConfig::default().with_addr_str("host:http").is_err());
+    }
+
+    #[test]
+    fn test_port_zero_rejected() {
+        let err = HttpServerConfig::default()
+            .with_addr_str("0.0.0.0:0")
+            .unwrap_err();
+        assert!(err.to_string().contains("port 0"));
+
+        let config = HttpServerConfig {
+            bind_address: "0.0.0.0:0".to_string(),
+            ..HttpServerConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let config = HttpServerConfig::from_toml_str(
+            r#"
+            bind_address = "127.0.0.1:8080"
+            route_prefix = "/api/"
+            timeout_ms = 10000
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.bind_address, "127.0.0.1:8080");
+        assert_eq!(config.route_prefix, "/api");
+        assert_eq!(config.timeout_ms, Some(10000));
+        assert_eq!(config.max_request_size, DEFAULT_MAX_REQUEST_SIZE);
+
+        assert!(HttpServerConfig::from_toml_str(r#"bind_address = "nope""#).is_err());
+        assert!(HttpServerConfig::from_toml_str(r#"unknown_key = 1"#).is_err());
+    }
+
+    #[test]
+    fn test_route_prefix_validation() {
+        for prefix in ["", "mcp", "/api/v1/", "mcp-v2_beta"] {
+            let config = HttpServerConfig::default().with_route_prefix(prefix);
+            assert!(config.validate().is_ok(), "{prefix}");
+        }
+        for prefix in [":id", "api/*rest", "{tool}", "a//b", "mcp.v1", "credits", "/health/x"] {
+            let config = HttpServerConfig::default().with_route_prefix(prefix);
+            assert!(config.validate().is_err(), "{prefix}");
+        }
+        let reserved = lookup(&[("MCP_HTTP_ROUTE_PREFIX", "admin")]);
+        assert!(HttpServerConfig::from_lookup(reserved).is_err());
+    }
+
+    #[test]
+    fn test_normalize_route_prefix() {
+        assert_eq!(normalize_route_prefix(""), "");
+        assert_eq!(normalize_route_prefix("/"), "");
+        assert_eq!(normalize_route_prefix("mcp"), "/mcp");
+        assert_eq!(normalize_route_prefix("/mcp/"), "/mcp");
+        assert_eq!(normalize_route_prefix(" api/v1/ "), "/api/v1");
+    }
+}
@@ -0,0 +1,427 @@
+//! Serving the HTTP router: connection limits, timeouts and graceful shutdown
+
+use crate::mcp::http_config::HttpServerConfig;
+use axum::{extract::ConnectInfo, Router};
+use hyper::{body::Incoming, server::conn::http1, Request};
+use hyper_util::rt::{TokioIo, TokioTimer};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{watch, Semaphore};
+use tokio::time::Instant;
+use tower::ServiceExt;
+use tracing::{debug, error, info, warn};
+
+/// Refused connections answered with 503 at once; past this, surplus
+/// connections are closed without a response
+const MAX_PENDING_REFUSALS: usize = 64;
+
+/// Per-connection limits applied by [`serve_until`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConnectionLimits {
+    /// Open connections at once; surplus connections get 503 and are closed
+    pub max_connections: Option<usize>,
+    /// Close connections that have not sent full request headers by then
+    pub header_read_timeout: Option<Duration>,
+    /// Close keep-alive connections with no request for this long
+    pub idle_timeout: Option<Duration>,
+}
+
+impl From<&HttpServerConfig> for ConnectionLimits {
+    fn from(config: &HttpServerConfig) -> Self {
+        Self {
+            max_connections: config.max_connections,
+            header_read_timeout: config.header_read_timeout_ms.map(Duration::from_millis),
+            idle_timeout: config.idle_timeout_ms.map(Duration::from_millis),
+        }
+    }
+}
+
+/// Request activity on one connection, for the idle timeout
+struct Activity {
+    in_flight: AtomicUsize,
+    requests: AtomicUsize,
+    last: Mutex<Instant>,
+}
+
+impl Activity {
+    fn new() -> Self {
+        Self {
+            in_flight: AtomicUsize::new(0),
+            requests: AtomicUsize::new(0),
+            last: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn touch(&self) {
+        *self.last.lock().unwrap() = Instant::now();
+    }
+
+    /// Resolves once no request has been in flight for `timeout`
+    async fn idle_for(&self, timeout: Duration) {
+        loop {
+            let now = Instant::now();
+            let deadline = if self.in_flight.load(Ordering::SeqCst) > 0 {
+                now + timeout
+            } else {
+                *self.last.lock().unwrap() + timeout
+            };
+            if deadline <= now {
+                return;
+            }
+            tokio::time::sleep_until(deadline).await;
+        }
+    }
+}
+
+/// Marks a request in flight until dropped
+struct InFlight(Arc<Activity>);
+
+impl InFlight {
+    fn start(activity: Arc<Activity>) -> Self {
+        activity.requests.fetch_add(1, Ordering::SeqCst);
+        activity.in_flight.fetch_add(1, Ordering::SeqCst);
+        activity.touch();
+        Self(activity)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.0.touch();
+    }
+}
+
+/// Serve until `shutdown` resolves, then stop accepting connections and let
+/// in-flight requests finish before returning.
+pub async fn serve_until<F>(
+    listener: TcpListener,
+    app: Router,
+    limits: ConnectionLimits,
+    shutdown: F,
+) -> anyhow::Result<()>
+where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    let permits = limits.max_connections.map(|max| Arc::new(Semaphore::new(max)));
+    let refusals = Arc::new(Semaphore::new(MAX_PENDING_REFUSALS));
+    // Every connection task holds a receiver; the sender sees them all gone
+    let (close_tx, close_rx) = watch::channel(());
+    tokio::pin!(shutdown);
+
+    loop {
+        let (stream, remote) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!("Failed to accept connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let permit = match &permits {
+            Some(permits) => match permits.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    warn!("Refusing connection from {}: connection limit reached", remote);
+                    // Dropping the stream closes it when too many refusals are pending
+                    if let Ok(refusal) = refusals.clone().try_acquire_owned() {
+                        tokio::spawn(async move {
+                            refuse_connection(stream).await;
+                            drop(refusal);
+                        });
+                    }
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        let app = app.clone();
+        let close_rx = close_rx.clone();
+        tokio::spawn(async move {
+            serve_connection(stream, remote, app, limits, close_rx).await;
+            drop(permit);
+        });
+    }
+
+    drop(listener);
+    drop(close_rx);
+    let _ = close_tx.send(());
+    close_tx.closed().await;
+    info!("HTTP server stopped");
+    Ok(())
+}
+
+/// Serve one connection until the client closes it, it times out, or the
+/// server shuts down
+async fn serve_connection(
+    stream: TcpStream,
+    remote: SocketAddr,
+    app: Router,
+    limits: ConnectionLimits,
+    mut close_rx: watch::Receiver<()>,
+) {
+    let activity = Arc::new(Activity::new());
+    let service = {
+        let activity = activity.clone();
+        hyper::service::service_fn(move |mut request: Request<Incoming>| {
+            request.extensions_mut().insert(ConnectInfo(remote));
+            let in_flight = InFlight::start(activity.clone());
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await;
+                drop(in_flight);
+                response
+            }
+        })
+    };
+
+    let mut builder = http1::Builder::new();
+    builder
+        .timer(TokioTimer::new())
+        .header_read_timeout(limits.header_read_timeout);
+    let connection = builder.serve_connection(TokioIo::new(stream), service);
+    tokio::pin!(connection);
+
+    let idle = async {
+        match limits.idle_timeout {
+            Some(timeout) => activity.idle_for(timeout).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(idle);
+
+    let mut closing = false;
+    loop {
+        tokio::select! {
+            result = connection.as_mut() => {
+                if let Err(e) = result {
+                    debug!("Connection from {} ended: {}", remote, e);
+                }
+                return;
+            }
+            _ = close_rx.changed(), if !closing => {}
+            _ = &mut idle, if !closing => debug!("Closing idle connection from {}", remote),
+        }
+        // A connection that never sent a request has nothing to drain
+        if activity.requests.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        connection.as_mut().graceful_shutdown();
+        closing = true;
+    }
+}
+
+/// Answer a connection over the limit with 503 and close it, giving up
+/// after a second so a slow client cannot hold the refusal open
+async fn refuse_connection(mut stream: TcpStream) {
+    let body = json!({
+        "success": false,
+        "error": "Server is at capacity, retry later"
+    })
+    .to_string();
+    let response = format!(
+        "HTTP/1.1 503 Service Unavailable\r\ncontent-type: application/json\r\n\
+         content-length: {}\r\nretry-after: 1\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+
+    let _ = tokio::time::timeout(Duration::from_secs(1), async {
+        if stream.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+        let _ = stream.shutdown().await;
+
+        // Read what the client sent so closing does not reset the connection
+        // before it sees the response
+        let mut sink = [0u8; 1024];
+        while matches!(stream.read(&mut sink).await, Ok(n) if n > 0) {}
+    })
+    .await;
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+
+    info!("Shutdown signal received, draining in-flight requests");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::get};
+
+    /// Serve `app` on a local port until the returned sender fires
+    async fn spawn_server(
+        app: Router,
+        limits: ConnectionLimits,
+    ) -> (
+        SocketAddr,
+        tokio::sync::oneshot::Sender<()>,
+        tokio::task::JoinHandle<anyhow::Result<()>>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve_until(listener, app, limits, async {
+            let _ = shutdown_rx.await;
+        }));
+        (addr, shutdown_tx, server)
+    }
+
+    /// Read until the server closes the connection, failing after 2 seconds
+    async fn read_until_closed(stream: &mut TcpStream) -> String {
+        let mut buf = Vec::new();
+        tokio::time::timeout(Duration::from_secs(2), stream.read_to_end(&mut buf))
+            .await
+            .expect("server kept the connection open")
+            .unwrap();
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown_drains_in_flight_request() {
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel::<()>();
+        let started_tx = Arc::new(Mutex::new(Some(started_tx)));
+        let app = Router::new().route(
+            "/slow",
+            get(move || {
+                let started_tx = started_tx.clone();
+                async move {
+                    if let Some(tx) = started_tx.lock().unwrap().take() {
+                        let _ = tx.send(());
+                    }
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    "done"
+                }
+            }),
+        );
+        let (addr, shutdown_tx, server) = spawn_server(app, ConnectionLimits::default()).await;
+
+        let request = tokio::spawn(async move {
+            reqwest::get(format!("http://{addr}/slow"))
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap()
+        });
+
+        // Trigger shutdown while the request is still being handled
+        started_rx.await.unwrap();
+        shutdown_tx.send(()).unwrap();
+
+        assert_eq!(request.await.unwrap(), "done");
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server did not stop")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connection_limit_refuses_surplus() {
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        let limits = ConnectionLimits {
+            max_connections: Some(1),
+            ..ConnectionLimits::default()
+        };
+        let (addr, _shutdown_tx, _server) = spawn_server(app, limits).await;
+
+        // An open connection takes the only slot, even before it sends a request
+        let held = TcpStream::connect(addr).await.unwrap();
+
+        let surplus = reqwest::get(format!("http://{addr}/")).await.unwrap();
+        assert_eq!(surplus.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = surplus.json().await.unwrap();
+        assert_eq!(body["error"], "Server is at capacity, retry later");
+
+        // Closing it frees the slot
+        drop(held);
+        let mut status = None;
+        for _ in 0..50 {
+            let response = reqwest::get(format!("http://{addr}/")).await.unwrap();
+            status = Some(response.status());
+            if response.status() == StatusCode::OK {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(status, Some(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_is_closed() {
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        let limits = ConnectionLimits {
+            idle_timeout: Some(Duration::from_millis(100)),
+            ..ConnectionLimits::default()
+        };
+        let (addr, _shutdown_tx, _server) = spawn_server(app, limits).await;
+
+        // Never sends a request
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        assert_eq!(read_until_closed(&mut stream).await, "");
+
+        // Served one keep-alive request, then went quiet
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let response = read_until_closed(&mut stream).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn test_header_read_timeout_closes_slow_client() {
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        let limits = ConnectionLimits {
+            header_read_timeout: Some(Duration::from_millis(100)),
+            ..ConnectionLimits::default()
+        };
+        let (addr, _shutdown_tx, _server) = spawn_server(app, limits).await;
+
+        // Starts a request but never finishes the headers
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+        let response = read_until_closed(&mut stream).await;
+        assert!(!response.contains("200 OK"), "{response}");
+    }
+}
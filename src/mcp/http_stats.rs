@@ -0,0 +1,136 @@
+//! HTTP-level request counters reported by `/stats`
+//!
+//! Counted by middleware so timeouts, rejections and other responses that
+//! never reach the protocol handler are included.
+
+use crate::mcp::stats::ServerStatsProvider;
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Request counters kept by the HTTP layer, by response status class
+#[derive(Debug, Default)]
+pub struct HttpStats {
+    total_requests: AtomicU64,
+    client_errors: AtomicU64,
+    server_errors: AtomicU64,
+}
+
+impl HttpStats {
+    pub fn record(&self, status: StatusCode) {
+        self.total_requests.fetch_add(1, Ordering::Relaxed);
+        if status.is_client_error() {
+            self.client_errors.fetch_add(1, Ordering::Relaxed);
+        } else if status.is_server_error() {
+            self.server_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "total_requests": self.total_requests.load(Ordering::Relaxed),
+            "client_errors": self.client_errors.load(Ordering::Relaxed),
+            "server_errors": self.server_errors.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// Count every response by status class, including timeouts
+pub async fn record_http_stats(
+    State(stats): State<Arc<HttpStats>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    stats.record(response.status());
+    response
+}
+
+/// The wrapped server's snapshot merged with the HTTP counters
+pub fn merged_stats(server: &dyn ServerStatsProvider, http: &HttpStats) -> Value {
+    json!({
+        "server": server.stats_snapshot(),
+        "http": http.to_json(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::http_config::HttpServerConfig;
+    use crate::mcp::http_stream_server::{build_router, AppState};
+    use crate::mcp::protocol_handler::ProtocolHandler;
+    use crate::mcp::stats::ServerStatsSnapshot;
+    use tower::ServiceExt;
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    struct DummyServer;
+
+    impl ServerStatsProvider for DummyServer {
+        fn stats_snapshot(&self) -> ServerStatsSnapshot {
+            ServerStatsSnapshot {
+                uptime_seconds: 42,
+                total_requests: 3,
+                total_errors: 1,
+                per_tool_counts: [("db".to_string(), 2)].into_iter().collect(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_merged_stats() {
+        let http = HttpStats::default();
+        http.record(StatusCode::OK);
+        http.record(StatusCode::NOT_FOUND);
+        http.record(StatusCode::BAD_GATEWAY);
+
+        let stats = merged_stats(&DummyServer, &http);
+        assert_eq!(
+            stats,
+            json!({
+                "server": {
+                    "uptime_seconds": 42,
+                    "total_requests": 3,
+                    "total_errors": 1,
+                    "per_tool_counts": { "db": 2 }
+                },
+                "http": {
+                    "total_requests": 3,
+                    "client_errors": 1,
+                    "server_errors": 1
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_route_counts_requests() {
+        let app = build_router(
+            AppState::new(Arc::new(ProtocolHandler::new())),
+            &HttpServerConfig::default(),
+        );
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        app.clone().oneshot(get("/health")).await.unwrap();
+        // GET on a POST-only route is rejected with 405
+        app.clone().oneshot(get("/rpc")).await.unwrap();
+        let stats = body_json(app.oneshot(get("/stats")).await.unwrap()).await;
+
+        assert_eq!(stats["http"]["total_requests"], 2);
+        assert_eq!(stats["http"]["client_errors"], 1);
+        assert!(stats["server"]["uptime_seconds"].is_u64());
+    }
+}
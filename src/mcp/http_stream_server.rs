@@ -5,106 +5,306 @@
 //! - /rpc - JSON-RPC endpoint (MCP protocol)
 //! - /tools - List available tools
 //! - /tools/call - Call a tool
+//! - /tools/:name - Call a tool with query-string arguments (GET)
+//! - /tools/call_stream - Stream `db` query rows as NDJSON
+//! - /stats - Server and HTTP request counters
+//! - /admin/shutdown - Graceful shutdown (only when an admin token is set)
+//! - /openapi.json - OpenAPI document (only when enabled)
 
+use crate::auth::middleware::OptionalAuthToken;
+use crate::mcp::admin::admin_shutdown_handler;
+use crate::mcp::authorization::ToolPermissions;
+use crate::mcp::deadline::{stamp_deadline, RequestDeadline};
+use crate::mcp::http_config::HttpServerConfig;
+use crate::mcp::http_serve::{serve_until, shutdown_signal, ConnectionLimits};
+use crate::mcp::http_stats::{merged_stats, record_http_stats, HttpStats};
+use crate::mcp::negotiate::{negotiated, negotiated_cached};
+use crate::mcp::openapi::openapi_document;
 use crate::mcp::protocol_handler::ProtocolHandler;
+use crate::mcp::rate_limit::{rate_limit, RateLimiter};
+#[cfg(feature = "postgres")]
+use crate::mcp::readiness::PostgRestReadiness;
+use crate::mcp::readiness::{health_response, Readiness, ReadinessCheck};
+use crate::mcp::request_id::with_request_id;
+use crate::mcp::tool_routes::{
+    call_context, call_tool_handler, call_tool_query_handler, call_tool_stream_handler,
+};
 use crate::credits::routes::credit_routes;
 use axum::{
-    extract::{Json, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Extension, Json, State},
+    http::{HeaderMap, StatusCode},
+    middleware,
     response::{IntoResponse, Response},
     routing::{get, post},
     Router,
 };
 use serde_json::{json, Value};
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tower_http::{
+    cors::{Any, CorsLayer},
+    timeout::TimeoutLayer,
+};
 use tracing::info;
 
 /// HTTP streaming server state
 #[derive(Clone)]
 pub struct AppState {
     pub protocol_handler: Arc<ProtocolHandler>,
+    /// Optional readiness probe consulted by `/health`
+    pub readiness: Option<Arc<dyn ReadinessCheck>>,
+    /// HTTP-level counters reported by `/stats`
+    pub http_stats: Arc<HttpStats>,
+    /// Signalled by `POST /admin/shutdown`
+    pub shutdown: Arc<Notify>,
+    /// Prefix the MCP routes are mounted under, set by [`build_router`]
+    pub route_prefix: Arc<str>,
+}
+
+impl AppState {
+    pub fn new(protocol_handler: Arc<ProtocolHandler>) -> Self {
+        Self {
+            protocol_handler,
+            readiness: None,
+            http_stats: Arc::new(HttpStats::default()),
+            shutdown: Arc::new(Notify::new()),
+            route_prefix: Arc::from(""),
+        }
+    }
+
+    /// Attach a readiness probe; `/health` returns 503 while it reports degraded
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    pub fn with_readiness(mut self, readiness: Arc<dyn ReadinessCheck>) -> Self {
+        self.readiness = Some(readiness);
+        self
+    }
 }
 
 /// Start HTTP streaming server
-pub async fn run_http_stream_server(bind_address: &str) -> anyhow::Result<()> {
+pub async fn run_http_stream_server(config: HttpServerConfig) -> anyhow::Result<()> {
+    config.validate()?;
+
+    let bind_address = config.bind_address.as_str();
+    let prefix = config.route_prefix.as_str();
+
     info!("Starting mcp-dautruongvui-be HTTP server");
     info!("Bind address: {}", bind_address);
 
-    let protocol_handler = Arc::new(ProtocolHandler::new());
+    let protocol_handler =
+        Arc::new(ProtocolHandler::new().with_tool_permissions(ToolPermissions::from_env()?));
 
-    let state = AppState { protocol_handler };
+    let state = AppState::new(protocol_handler);
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    #[cfg(feature = "postgres")]
+    let state = state.with_readiness(Arc::new(PostgRestReadiness));
 
-    let app = Router::new()
-        .route("/", get(root_handler))
-        .route("/health", get(health_handler))
-        .route("/rpc", post(rpc_handler))
-        .route("/tools", get(list_tools_handler))
-        .route("/tools/call", post(call_tool_handler))
-        .nest("/credits", credit_routes().with_state(()))
-        .route("/upload", post(upload_proxy_handler))
-        .layer(cors)
-        .with_state(state);
+    let shutdown = state.shutdown.clone();
+    let app = build_router(state, &config);
+
+    let root = if prefix.is_empty() { "/" } else { prefix };
 
     info!("HTTP server ready on http://{}", bind_address);
     info!("Endpoints:");
-    info!("  GET  /                          - Server info");
-    info!("  GET  /health                    - Health check");
-    info!("  POST /rpc                       - JSON-RPC endpoint");
-    info!("  GET  /tools                     - List tools");
-    info!("  POST /tools/call                - Call a tool");
+    info!("  GET  {:<32}- Server info", root);
+    info!("  GET  {:<32}- Health check", "/health");
+    info!("  POST {:<32}- JSON-RPC endpoint", format!("{prefix}/rpc"));
+    info!("  GET  {:<32}- List tools", format!("{prefix}/tools"));
+    info!("  POST {:<32}- Call a tool", format!("{prefix}/tools/call"));
+    info!(
+        "  GET  {:<32}- Call a tool with query-string args",
+        format!("{prefix}/tools/:name")
+    );
+    info!(
+        "  POST {:<32}- Stream db query rows as NDJSON",
+        format!("{prefix}/tools/call_stream")
+    );
+    info!("  GET  {:<32}- Server and HTTP stats", format!("{prefix}/stats"));
+    if config.enable_openapi {
+        info!("  GET  {:<32}- OpenAPI document", format!("{prefix}/openapi.json"));
+    }
     info!("  POST /credits/wallet            - Get/create credit wallet");
     info!("  POST /credits/deduct            - Deduct credits");
     info!("  POST /credits/claim-welcome-bonus - Claim welcome bonus");
     info!("  POST /credits/claim-daily-bonus   - Claim daily bonus");
     info!("  POST /upload                      - S3 file upload via V5 proxy");
+    if config.admin_token.is_some() {
+        info!("  POST /admin/shutdown              - Graceful shutdown (admin token)");
+    }
 
     let listener = tokio::net::TcpListener::bind(bind_address).await?;
-    axum::serve(listener, app).await?;
+    serve_until(listener, app, ConnectionLimits::from(&config), async move {
+        tokio::select! {
+            _ = shutdown_signal() => {}
+            _ = shutdown.notified() => {
+                info!("Shutdown requested via /admin/shutdown, draining in-flight requests");
+            }
+        }
+    })
+    .await
+}
 
-    Ok(())
+/// Build the application router with CORS and request-id tracing layers
+/// (see [`with_request_id`]).
+///
+/// MCP routes are mounted under `config.route_prefix`; `/health`, `/credits`
+/// and `/upload` always stay at the root.
+pub fn build_router(mut state: AppState, config: &HttpServerConfig) -> Router {
+    let route_prefix = config.route_prefix.as_str();
+    state.route_prefix = Arc::from(route_prefix);
+
+    let mcp_routes = Router::new()
+        .route("/", get(root_handler))
+        .route("/rpc", post(rpc_handler))
+        .route("/tools", get(list_tools_handler))
+        .route("/tools/call", post(call_tool_handler))
+        .route("/tools/call_stream", post(call_tool_stream_handler))
+        .route("/tools/:name", get(call_tool_query_handler))
+        .route("/stats", get(stats_handler));
+
+    let mcp_routes = if config.enable_openapi {
+        let document = Arc::new(openapi_document(config, &state.protocol_handler.list_tools()));
+        mcp_routes.route(
+            "/openapi.json",
+            get(move || {
+                let document = document.clone();
+                async move { Json(document.as_ref().clone()) }
+            }),
+        )
+    } else {
+        mcp_routes
+    };
+
+    let router = if route_prefix.is_empty() {
+        Router::new().merge(mcp_routes)
+    } else {
+        Router::new().nest(route_prefix, mcp_routes)
+    };
+
+    let router = router
+        .route("/health", get(health_handler))
+        .nest("/credits", credit_routes().with_state(()))
+        .route("/upload", post(upload_proxy_handler));
+
+    let router = match &config.admin_token {
+        Some(token) => {
+            let token: Arc<str> = Arc::from(token.as_str());
+            router.route(
+                "/admin/shutdown",
+                post(move |State(state): State<AppState>, headers: HeaderMap| {
+                    admin_shutdown_handler(state.shutdown, headers, token.clone())
+                }),
+            )
+        }
+        None => router,
+    };
+
+    let router = router.layer(DefaultBodyLimit::max(config.max_request_size));
+
+    let default_timeout = config.timeout_ms.map(Duration::from_millis);
+    let router = match default_timeout {
+        Some(timeout) => router.layer(TimeoutLayer::new(timeout)),
+        None => router,
+    };
+
+    // Outside the timeout layer so tool deadlines never outlive it
+    let router = router.layer(middleware::from_fn_with_state(default_timeout, stamp_deadline));
+
+    let router = match config.rate_limit_per_second {
+        Some(per_second) => {
+            let burst = config.rate_limit_burst.unwrap_or(per_second);
+            router.layer(middleware::from_fn_with_state(
+                Arc::new(RateLimiter::new(per_second, burst)),
+                rate_limit,
+            ))
+        }
+        None => router,
+    };
+
+    let router = router.layer(middleware::from_fn_with_state(
+        state.http_stats.clone(),
+        record_http_stats,
+    ));
+
+    let router = if config.cors {
+        router.layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any),
+        )
+    } else {
+        router
+    };
+
+    let router = with_request_id(router, config.logging);
+
+    router.with_state(state)
 }
 
 /// Root handler - server information
-async fn root_handler() -> Json<Value> {
-    Json(json!({
+///
+/// The `ETag` covers everything except the timestamp, so it changes only
+/// when the server version or tool list does.
+async fn root_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let tool_names = state.protocol_handler.tool_names();
+    let prefix = state.route_prefix.as_ref();
+
+    let mut info = json!({
         "service": "mcp-dautruongvui-be",
         "version": env!("CARGO_PKG_VERSION"),
         "transport": "http-stream",
+        "tool_count": tool_names.len(),
+        "tool_names": tool_names,
         "endpoints": {
             "health": "/health",
-            "rpc": "/rpc",
-            "tools": "/tools",
-            "tools_call": "/tools/call"
-        },
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    }))
+            "rpc": format!("{prefix}/rpc"),
+            "tools": format!("{prefix}/tools"),
+            "tools_call": format!("{prefix}/tools/call"),
+            "tools_call_query": format!("{prefix}/tools/:name"),
+            "tools_call_stream": format!("{prefix}/tools/call_stream"),
+            "stats": format!("{prefix}/stats")
+        }
+    });
+    let etag_source = info.clone();
+    info["timestamp"] = json!(chrono::Utc::now().to_rfc3339());
+
+    negotiated_cached(&headers, &info, &etag_source)
+}
+
+/// Stats handler - the wrapped server's snapshot merged with HTTP counters
+async fn stats_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let stats = merged_stats(state.protocol_handler.as_ref(), &state.http_stats);
+    negotiated(&headers, StatusCode::OK, &stats)
 }
 
 /// Health check handler
-async fn health_handler() -> Json<Value> {
-    Json(json!({
-        "status": "ok",
-        "service": "mcp-dautruongvui-be",
-        "version": env!("CARGO_PKG_VERSION"),
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    }))
+///
+/// Returns 503 with the reason when the configured readiness probe reports
+/// the server as degraded.
+async fn health_handler(State(state): State<AppState>) -> Response {
+    let readiness = match &state.readiness {
+        Some(probe) => probe.readiness().await,
+        None => Readiness::Ready,
+    };
+    health_response(readiness)
 }
 
-/// RPC handler - JSON-RPC over HTTP
+/// RPC handler - JSON-RPC over HTTP (single request or batch array).
+/// The body is passed through as-is, so any `Content-Type` is accepted.
 async fn rpc_handler(
     State(state): State<AppState>,
-    Json(request): Json<Value>,
+    OptionalAuthToken(claims): OptionalAuthToken,
+    deadline: Option<Extension<RequestDeadline>>,
+    body: Bytes,
 ) -> Response {
-    let request_str = serde_json::to_string(&request).unwrap_or_default();
+    let ctx = call_context(claims, deadline);
+    // Unparsable bodies reach the handler so they get a JSON-RPC -32700
+    let request_str = String::from_utf8_lossy(&body);
     let response_str = state
         .protocol_handler
-        .handle_request(&request_str)
+        .handle_request_with(&request_str, &ctx)
         .await
         .unwrap_or_else(|e| {
             json!({
@@ -117,19 +317,21 @@ async fn rpc_handler(
             .to_string()
         });
 
-    let response: Value =
-        serde_json::from_str(&response_str).unwrap_or_else(|_| json!({}));
+    // Notifications and notification-only batches have nothing to return
+    if response_str.is_empty() {
+        return StatusCode::NO_CONTENT.into_response();
+    }
 
     (
         StatusCode::OK,
         [(axum::http::header::CONTENT_TYPE, "application/json")],
-        Json(response),
+        response_str,
     )
         .into_response()
 }
 
-/// List tools handler
-async fn list_tools_handler(State(state): State<AppState>) -> Json<Value> {
+/// List tools handler (supports `If-None-Match`)
+async fn list_tools_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
     let request = json!({
         "jsonrpc": "2.0",
         "id": 1,
@@ -145,44 +347,7 @@ async fn list_tools_handler(State(state): State<AppState>) -> Json<Value> {
         .unwrap_or_default();
     let response: Value =
         serde_json::from_str(&response_str).unwrap_or_else(|_| json!({}));
-    Json(response)
-}
-
-/// Call tool handler
-async fn call_tool_handler(
-    State(state): State<AppState>,
-    Json(payload): Json<Value>,
-) -> Response {
-    let tool_name = payload["name"].as_str().unwrap_or("unknown");
-    let arguments = &payload["arguments"];
-
-    info!("Tool call: {} with args: {}", tool_name, arguments);
-
-    let request = json!({
-        "jsonrpc": "2.0",
-        "id": 1,
-        "method": "tools/call",
-        "params": {
-            "name": tool_name,
-            "arguments": arguments
-        }
-    });
-
-    let request_str = serde_json::to_string(&request).unwrap();
-    let response_str = state
-        .protocol_handler
-        .handle_request(&request_str)
-        .await
-        .unwrap_or_default();
-    let response: Value =
-        serde_json::from_str(&response_str).unwrap_or_else(|_| json!({}));
-
-    (
-        StatusCode::OK,
-        [(axum::http::header::CONTENT_TYPE, "application/json")],
-        Json(response),
-    )
-        .into_response()
+    negotiated_cached(&headers, &response, &response)
 }
 
 /// Upload proxy handler -- delegates to upload::routes module
@@ -211,10 +376,124 @@ async fn upload_proxy_handler(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::tool_set::testing::StubTools;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
 
     #[test]
     fn test_app_state_creation() {
         let protocol_handler = Arc::new(ProtocolHandler::new());
-        let _state = AppState { protocol_handler };
+        let _state = AppState::new(protocol_handler);
+    }
+
+    #[tokio::test]
+    async fn test_route_prefix_mounts_mcp_routes() {
+        let config = HttpServerConfig::default().with_route_prefix("mcp/");
+        let app = build_router(AppState::new(Arc::new(ProtocolHandler::new())), &config);
+
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(get("/mcp/tools")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.clone().oneshot(get("/tools")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app.clone().oneshot(get("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Server info advertises the mounted paths
+        let response = app.oneshot(get("/mcp")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let endpoints = body_json(response).await["endpoints"].clone();
+        assert_eq!(endpoints["health"], "/health");
+        assert_eq!(endpoints["rpc"], "/mcp/rpc");
+        assert_eq!(endpoints["tools_call_query"], "/mcp/tools/:name");
+        assert_eq!(endpoints["stats"], "/mcp/stats");
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_rpc_malformed_body_is_parse_error() {
+        let app = build_router(
+            AppState::new(Arc::new(ProtocolHandler::new())),
+            &HttpServerConfig::default(),
+        );
+        // No Content-Type either: the handler, not axum, rejects it
+        let request = Request::builder()
+            .method("POST")
+            .uri("/rpc")
+            .body(Body::from("{bad json"))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["error"]["code"], -32700);
+        assert!(body["id"].is_null());
+        assert!(body.as_object().unwrap().contains_key("id"));
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_root_handler_lists_tools() {
+        let handler = ProtocolHandler::new().with_tools(Arc::new(StubTools));
+        let state = AppState::new(Arc::new(handler));
+
+        let info = body_json(root_handler(State(state), HeaderMap::new()).await).await;
+        assert_eq!(info["tool_names"], json!(["echo", "slow", "boom"]));
+        assert_eq!(info["tool_count"], 3);
+    }
+
+    struct DegradedProbe;
+
+    #[async_trait::async_trait]
+    impl ReadinessCheck for DegradedProbe {
+        async fn readiness(&self) -> Readiness {
+            Readiness::Degraded {
+                reason: "database unreachable".to_string(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_consults_probe() {
+        let state = AppState::new(Arc::new(ProtocolHandler::new()));
+        let response = health_handler(State(state)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let state = AppState::new(Arc::new(ProtocolHandler::new()))
+            .with_readiness(Arc::new(DegradedProbe));
+        let response = health_handler(State(state)).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body_json(response).await["reason"], "database unreachable");
+    }
+
+    #[tokio::test]
+    async fn test_tools_conditional_get() {
+        let app = build_router(
+            AppState::new(Arc::new(ProtocolHandler::new())),
+            &HttpServerConfig::default(),
+        );
+
+        for uri in ["/", "/tools"] {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let etag = response.headers()[axum::http::header::ETAG].clone();
+
+            let request = Request::builder()
+                .uri(uri)
+                .header(axum::http::header::IF_NONE_MATCH, etag)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        }
+    }
+}
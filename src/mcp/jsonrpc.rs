@@ -0,0 +1,70 @@
+//! JSON-RPC 2.0 envelope helpers shared by the protocol handler
+
+use serde_json::{json, Value};
+use tracing::error;
+
+/// Build a JSON-RPC success response
+pub fn result_response(id: Option<Value>, result: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result
+    })
+}
+
+/// Build a JSON-RPC error response
+pub fn error_response(id: Option<Value>, code: i32, message: String) -> Value {
+    error!("Error {}: {}", code, message);
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message
+        }
+    })
+}
+
+/// Method of a well-formed JSON-RPC 2.0 request, or the reason it is an
+/// invalid request (-32600)
+pub fn request_method(request: &Value) -> Result<&str, String> {
+    if request.get("jsonrpc").and_then(|v| v.as_str()) != Some("2.0") {
+        return Err("Invalid Request: expected jsonrpc \"2.0\"".to_string());
+    }
+    request
+        .get("method")
+        .and_then(|m| m.as_str())
+        .ok_or_else(|| "Invalid Request: missing method".to_string())
+}
+
+/// Whether `request` is a notification: a valid request without an `id`.
+/// Invalid requests are answered with an error even when they lack one.
+pub fn is_notification(request: &Value) -> bool {
+    request_method(request).is_ok() && request.get("id").is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_method() {
+        assert_eq!(
+            request_method(&json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" })),
+            Ok("ping")
+        );
+        assert!(request_method(&json!({ "id": 7, "method": "ping" })).is_err());
+        assert!(request_method(&json!({ "jsonrpc": "1.0", "method": "ping" })).is_err());
+        assert!(request_method(&json!({ "jsonrpc": "2.0", "id": 8 })).is_err());
+        assert!(request_method(&json!(42)).is_err());
+    }
+
+    #[test]
+    fn test_is_notification() {
+        assert!(is_notification(&json!({ "jsonrpc": "2.0", "method": "ping" })));
+        assert!(!is_notification(&json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" })));
+        assert!(!is_notification(&json!({ "jsonrpc": "1.0", "method": "ping" })));
+        assert!(!is_notification(&json!({ "jsonrpc": "2.0" })));
+        assert!(!is_notification(&json!(42)));
+    }
+}
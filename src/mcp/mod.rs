@@ -1,10 +1,61 @@
+pub mod authorization;
+pub mod jsonrpc;
+pub mod pagination;
 pub mod protocol_handler;
+pub mod server_info;
+pub mod stats;
 pub mod stdio_server;
+pub mod tool_call;
+pub mod tool_schema;
+pub mod tool_set;
+pub mod tool_task;
+
+#[cfg(feature = "http-stream")]
+pub mod admin;
+
+#[cfg(feature = "http-stream")]
+pub mod deadline;
+
+#[cfg(feature = "http-stream")]
+pub mod http_config;
+
+#[cfg(feature = "http-stream")]
+pub mod http_serve;
+
+#[cfg(feature = "http-stream")]
+pub mod http_stats;
 
 #[cfg(feature = "http-stream")]
 pub mod http_stream_server;
 
+#[cfg(feature = "http-stream")]
+pub mod ndjson;
+
+#[cfg(feature = "http-stream")]
+pub mod negotiate;
+
+#[cfg(feature = "http-stream")]
+pub mod openapi;
+
+#[cfg(feature = "http-stream")]
+pub mod query_args;
+
+#[cfg(feature = "http-stream")]
+pub mod rate_limit;
+
+#[cfg(feature = "http-stream")]
+pub mod readiness;
+
+#[cfg(feature = "http-stream")]
+pub mod request_id;
+
+#[cfg(feature = "http-stream")]
+pub mod tool_routes;
+
 pub use stdio_server::McpServer;
 
+#[cfg(feature = "http-stream")]
+pub use http_config::HttpServerConfig;
+
 #[cfg(feature = "http-stream")]
 pub use http_stream_server::run_http_stream_server;
\ No newline at end of file
@@ -0,0 +1,151 @@
+//! NDJSON streaming helpers for the HTTP server
+//!
+//! Rows are fetched page by page and written one JSON object per line, so a
+//! large result set is never held in memory or serialized as a single body.
+#![cfg_attr(not(feature = "postgres"), allow(dead_code))]
+
+use axum::body::{Body, Bytes};
+use futures::stream::{self, Stream, StreamExt};
+use serde_json::{json, Value};
+use std::future::Future;
+
+/// Content type for newline-delimited JSON
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Default number of rows fetched per page
+pub const DEFAULT_PAGE_SIZE: u64 = 500;
+
+struct PageState<F> {
+    fetch: F,
+    offset: u64,
+    remaining: Option<u64>,
+    page_size: u64,
+    buffer: std::vec::IntoIter<Value>,
+    done: bool,
+}
+
+/// Stream rows from a paged source.
+///
+/// `fetch(offset, limit)` returns one page; a page shorter than `limit` ends
+/// the stream. `limit` caps the total number of rows (unbounded when `None`).
+/// A fetch error is yielded once and ends the stream.
+pub fn paged_rows<F, Fut>(
+    fetch: F,
+    start_offset: u64,
+    limit: Option<u64>,
+    page_size: u64,
+) -> impl Stream<Item = Result<Value, String>>
+where
+    F: FnMut(u64, u64) -> Fut,
+    Fut: Future<Output = Result<Vec<Value>, String>>,
+{
+    let state = PageState {
+        fetch,
+        offset: start_offset,
+        remaining: limit,
+        page_size: page_size.max(1),
+        buffer: Vec::new().into_iter(),
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(row) = state.buffer.next() {
+                return Some((Ok(row), state));
+            }
+            if state.done || state.remaining == Some(0) {
+                return None;
+            }
+
+            let page_limit = match state.remaining {
+                Some(remaining) => remaining.min(state.page_size),
+                None => state.page_size,
+            };
+
+            match (state.fetch)(state.offset, page_limit).await {
+                Ok(rows) => {
+                    let fetched = rows.len() as u64;
+                    state.offset += fetched;
+                    state.remaining = state.remaining.map(|r| r.saturating_sub(fetched));
+                    state.done = fetched < page_limit;
+                    state.buffer = rows.into_iter();
+                }
+                Err(e) => {
+                    state.done = true;
+                    return Some((Err(e), state));
+                }
+            }
+        }
+    })
+}
+
+/// Encode a row stream as an NDJSON body. Errors become a final
+/// `{"error": "..."}` line since the status code has already been sent.
+pub fn ndjson_body<S>(rows: S) -> Body
+where
+    S: Stream<Item = Result<Value, String>> + Send + 'static,
+{
+    let lines = rows.map(|row| {
+        let value = row.unwrap_or_else(|e| json!({ "error": e }));
+        let mut line = serde_json::to_vec(&value).unwrap_or_default();
+        line.push(b'\n');
+        Ok::<_, std::convert::Infallible>(Bytes::from(line))
+    });
+    Body::from_stream(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: u64) -> impl FnMut(u64, u64) -> std::future::Ready<Result<Vec<Value>, String>> {
+        move |offset, limit| {
+            let end = (offset + limit).min(rows);
+            let page = (offset..end).map(|id| json!({ "id": id })).collect();
+            std::future::ready(Ok(page))
+        }
+    }
+
+    async fn collect_lines(body: Body) -> Vec<Value> {
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_streams_all_rows_across_pages() {
+        let lines = collect_lines(ndjson_body(paged_rows(table(25), 0, None, 10))).await;
+        assert_eq!(lines.len(), 25);
+        assert_eq!(lines[0]["id"], 0);
+        assert_eq!(lines[24]["id"], 24);
+    }
+
+    #[tokio::test]
+    async fn test_respects_offset_and_limit() {
+        let lines = collect_lines(ndjson_body(paged_rows(table(100), 5, Some(12), 10))).await;
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0]["id"], 5);
+        assert_eq!(lines[11]["id"], 16);
+    }
+
+    #[tokio::test]
+    async fn test_error_ends_stream() {
+        let mut calls = 0;
+        let fetch = move |offset: u64, limit: u64| {
+            calls += 1;
+            let result = if calls == 1 {
+                Ok((offset..offset + limit).map(|id| json!({ "id": id })).collect())
+            } else {
+                Err("connection reset".to_string())
+            };
+            std::future::ready(result)
+        };
+
+        let lines = collect_lines(ndjson_body(paged_rows(fetch, 0, None, 3))).await;
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3]["error"], "connection reset");
+    }
+}
@@ -0,0 +1,240 @@
+//! Response content negotiation for the HTTP server
+//!
+//! JSON by default; MessagePack when the client's `Accept` header asks for
+//! it. Anything else falls back to JSON rather than a 406. Read endpoints can
+//! also answer conditional GETs with `ETag`/`If-None-Match`. Every
+//! negotiated response carries `Vary: Accept` so caches keep the encodings
+//! apart.
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Response encodings the server can produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Json,
+    MsgPack,
+}
+
+impl Encoding {
+    /// Pick an encoding from the `Accept` header: the supported media range
+    /// with the highest `q` wins, earlier ranges break ties, and `q=0`
+    /// excludes a range
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let accept = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','));
+
+        let mut best: Option<(Encoding, f32)> = None;
+        for media_range in accept {
+            let mut parts = media_range.split(';');
+            let media_type = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+            let encoding = match media_type.as_str() {
+                "application/json" | "application/*" | "*/*" => Encoding::Json,
+                "application/msgpack" | "application/x-msgpack" => Encoding::MsgPack,
+                _ => continue,
+            };
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality > 0.0 && !matches!(best, Some((_, q)) if q >= quality) {
+                best = Some((encoding, quality));
+            }
+        }
+        best.map_or(Encoding::Json, |(encoding, _)| encoding)
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Encoding::Json => JSON_CONTENT_TYPE,
+            Encoding::MsgPack => MSGPACK_CONTENT_TYPE,
+        }
+    }
+}
+
+/// Serialize `body` in the encoding requested by `headers`
+pub fn negotiated<T: Serialize>(headers: &HeaderMap, status: StatusCode, body: &T) -> Response {
+    let encoding = Encoding::from_headers(headers);
+    let bytes = match encoding {
+        Encoding::Json => serde_json::to_vec(body).map_err(|e| e.to_string()),
+        Encoding::MsgPack => rmp_serde::to_vec_named(body).map_err(|e| e.to_string()),
+    };
+
+    let mut response = match bytes {
+        Ok(bytes) => (
+            status,
+            [(header::CONTENT_TYPE, HeaderValue::from_static(encoding.content_type()))],
+            bytes,
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Serialization error: {e}"),
+        )
+            .into_response(),
+    };
+    response.headers_mut().insert(header::VARY, vary_accept());
+    response
+}
+
+fn vary_accept() -> HeaderValue {
+    HeaderValue::from_static("accept")
+}
+
+/// Like [`negotiated`] with status 200, plus a weak `ETag` derived from
+/// `etag_source` and the chosen encoding. Returns 304 Not Modified when the
+/// client's `If-None-Match` already holds that tag.
+///
+/// `etag_source` should exclude volatile fields such as timestamps so the
+/// tag only changes when the content does.
+pub fn negotiated_cached<T: Serialize, K: Serialize>(
+    headers: &HeaderMap,
+    body: &T,
+    etag_source: &K,
+) -> Response {
+    let etag = etag_for(Encoding::from_headers(headers), etag_source);
+    let etag_value = HeaderValue::from_str(&etag).expect("etag is ASCII");
+
+    if if_none_match(headers, &etag) {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, etag_value), (header::VARY, vary_accept())],
+        )
+            .into_response();
+    }
+
+    let mut response = negotiated(headers, StatusCode::OK, body);
+    if response.status().is_success() {
+        response.headers_mut().insert(header::ETAG, etag_value);
+    }
+    response
+}
+
+fn etag_for<K: Serialize>(encoding: Encoding, source: &K) -> String {
+    let mut bytes = serde_json::to_vec(source).unwrap_or_default();
+    bytes.extend_from_slice(encoding.content_type().as_bytes());
+    let digest = openssl::sha::sha256(&bytes);
+    let hex: String = digest[..16].iter().map(|b| format!("{b:02x}")).collect();
+    format!("W/\"{hex}\"")
+}
+
+/// Whether `If-None-Match` matches `etag` (weak comparison)
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let wanted = opaque(etag);
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == wanted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_encoding_from_headers() {
+        assert_eq!(Encoding::from_headers(&HeaderMap::new()), Encoding::Json);
+        assert_eq!(Encoding::from_headers(&accept("application/json")), Encoding::Json);
+        assert_eq!(Encoding::from_headers(&accept("application/msgpack")), Encoding::MsgPack);
+        assert_eq!(
+            Encoding::from_headers(&accept("text/html, application/x-msgpack;q=0.9")),
+            Encoding::MsgPack
+        );
+        assert_eq!(Encoding::from_headers(&accept("text/html")), Encoding::Json);
+
+        // Highest q wins; q=0 excludes the range
+        assert_eq!(
+            Encoding::from_headers(&accept("application/msgpack;q=0, application/json")),
+            Encoding::Json
+        );
+        assert_eq!(
+            Encoding::from_headers(&accept("application/json;q=0.1, application/msgpack")),
+            Encoding::MsgPack
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_is_json() {
+        let body = json!({ "tools": ["db"], "count": 1 });
+        let response = negotiated(&HeaderMap::new(), StatusCode::OK, &body);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], JSON_CONTENT_TYPE);
+        assert_eq!(response.headers()[header::VARY], "accept");
+
+        let decoded: Value = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(decoded, body);
+    }
+
+    #[tokio::test]
+    async fn test_msgpack_round_trip() {
+        let body = json!({ "tools": ["db", "auth"], "count": 2, "ok": true });
+        let response = negotiated(&accept("application/msgpack"), StatusCode::OK, &body);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], MSGPACK_CONTENT_TYPE);
+        assert_eq!(response.headers()[header::VARY], "accept");
+
+        let decoded: Value = rmp_serde::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(decoded, body);
+    }
+
+    #[test]
+    fn test_etag_varies_with_content_and_encoding() {
+        let a = etag_for(Encoding::Json, &json!({ "tools": ["db"] }));
+        assert_eq!(a, etag_for(Encoding::Json, &json!({ "tools": ["db"] })));
+        assert_ne!(a, etag_for(Encoding::Json, &json!({ "tools": ["db", "auth"] })));
+        assert_ne!(a, etag_for(Encoding::MsgPack, &json!({ "tools": ["db"] })));
+        assert!(a.starts_with("W/\""));
+    }
+
+    #[tokio::test]
+    async fn test_conditional_get_returns_304() {
+        let body = json!({ "tools": ["db"] });
+        let response = negotiated_cached(&HeaderMap::new(), &body, &body);
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&etag).unwrap());
+        let response = negotiated_cached(&headers, &body, &body);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        assert_eq!(response.headers()[header::VARY], "accept");
+        assert!(body_bytes(response).await.is_empty());
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("W/\"stale\""));
+        let response = negotiated_cached(&headers, &body, &body);
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_accept_falls_back_to_json() {
+        let response = negotiated(&accept("application/xml"), StatusCode::OK, &json!({}));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], JSON_CONTENT_TYPE);
+    }
+}
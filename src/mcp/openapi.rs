@@ -0,0 +1,313 @@
+//! OpenAPI 3.0 description of the HTTP server
+//!
+//! Served at `/openapi.json` when `enable_openapi` is set. The `/tools/call`
+//! request schema is built from the registered tools' input schemas.
+
+use crate::mcp::http_config::HttpServerConfig;
+use rmcp::model::Tool;
+use serde_json::{json, Map, Value};
+
+fn json_response(description: &str, schema: Value) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } }
+    })
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn json_body(schema: Value) -> Value {
+    json!({
+        "required": true,
+        "content": { "application/json": { "schema": schema } }
+    })
+}
+
+/// `{"name": <tool>, "arguments": <input schema>}` for each tool
+fn tool_call_schema(tools: &[Tool]) -> Value {
+    let variants: Vec<Value> = tools
+        .iter()
+        .map(|tool| {
+            json!({
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": { "type": "string", "enum": [tool.name] },
+                    "arguments": Value::Object(tool.input_schema.as_ref().clone())
+                }
+            })
+        })
+        .collect();
+
+    if variants.is_empty() {
+        schema_ref("ToolCall")
+    } else {
+        json!({ "oneOf": variants })
+    }
+}
+
+/// Build the OpenAPI document for the routes `config` enables
+pub fn openapi_document(config: &HttpServerConfig, tools: &[Tool]) -> Value {
+    let prefix = config.route_prefix.as_str();
+    let root = if prefix.is_empty() { "/" } else { prefix };
+    let rpc_response = json_response("JSON-RPC response", schema_ref("JsonRpcResponse"));
+
+    let mut paths = Map::new();
+    paths.insert(
+        "/health".to_string(),
+        json!({ "get": {
+            "summary": "Health check",
+            "responses": {
+                "200": json_response("Healthy", schema_ref("Health")),
+                "503": json_response("Degraded", schema_ref("Health"))
+            }
+        }}),
+    );
+    paths.insert(
+        root.to_string(),
+        json!({ "get": {
+            "summary": "Server info",
+            "responses": { "200": json_response("Server info", json!({ "type": "object" })) }
+        }}),
+    );
+    paths.insert(
+        format!("{prefix}/rpc"),
+        json!({ "post": {
+            "summary": "JSON-RPC endpoint (single request or batch)",
+            "requestBody": json_body(schema_ref("JsonRpcRequest")),
+            "responses": {
+                "200": rpc_response.clone(),
+                "204": { "description": "Notifications only" }
+            }
+        }}),
+    );
+    paths.insert(
+        format!("{prefix}/tools"),
+        json!({ "get": {
+            "summary": "List tools",
+            "responses": { "200": rpc_response.clone(), "304": { "description": "Not modified" } }
+        }}),
+    );
+    paths.insert(
+        format!("{prefix}/tools/call"),
+        json!({ "post": {
+            "summary": "Call a tool",
+            "requestBody": json_body(tool_call_schema(tools)),
+            "responses": { "200": rpc_response.clone() }
+        }}),
+    );
+    paths.insert(
+        format!("{prefix}/tools/{{name}}"),
+        json!({ "get": {
+            "summary": "Call a read-only tool action with query-string arguments",
+            "parameters": [{
+                "name": "name",
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" }
+            }],
+            "responses": {
+                "200": rpc_response,
+                "400": json_response("Argument not coercible", schema_ref("Error")),
+                "404": json_response("Unknown tool", schema_ref("Error")),
+                "405": json_response("Call may change state; use POST", schema_ref("Error"))
+            }
+        }}),
+    );
+    paths.insert(
+        format!("{prefix}/tools/call_stream"),
+        json!({ "post": {
+            "summary": "Stream db query rows as NDJSON",
+            "requestBody": json_body(schema_ref("ToolCall")),
+            "responses": {
+                "200": {
+                    "description": "One JSON row per line",
+                    "content": { "application/x-ndjson": { "schema": { "type": "object" } } }
+                },
+                "400": json_response("Rejected before streaming", schema_ref("Error"))
+            }
+        }}),
+    );
+    paths.insert(
+        format!("{prefix}/stats"),
+        json!({ "get": {
+            "summary": "Server and HTTP request counters",
+            "responses": { "200": json_response("Stats", schema_ref("Stats")) }
+        }}),
+    );
+    if config.admin_token.is_some() {
+        paths.insert(
+            "/admin/shutdown".to_string(),
+            json!({ "post": {
+                "summary": "Graceful shutdown",
+                "security": [{ "adminToken": [] }],
+                "responses": {
+                    "202": json_response("Shutting down", json!({ "type": "object" })),
+                    "401": json_response("Invalid admin token", schema_ref("Error"))
+                }
+            }}),
+        );
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "mcp-dautruongvui-be",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                "adminToken": { "type": "http", "scheme": "bearer" }
+            },
+            "schemas": {
+                "ToolCall": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "arguments": { "type": "object" }
+                    }
+                },
+                "JsonRpcRequest": {
+                    "type": "object",
+                    "required": ["jsonrpc", "method"],
+                    "properties": {
+                        "jsonrpc": { "type": "string", "enum": ["2.0"] },
+                        "id": {},
+                        "method": { "type": "string" },
+                        "params": { "type": "object" }
+                    }
+                },
+                "JsonRpcResponse": {
+                    "type": "object",
+                    "required": ["jsonrpc"],
+                    "properties": {
+                        "jsonrpc": { "type": "string", "enum": ["2.0"] },
+                        "id": {},
+                        "result": {},
+                        "error": {
+                            "type": "object",
+                            "properties": {
+                                "code": { "type": "integer" },
+                                "message": { "type": "string" },
+                                "data": {}
+                            }
+                        }
+                    }
+                },
+                "Health": {
+                    "type": "object",
+                    "properties": {
+                        "status": { "type": "string", "enum": ["ok", "degraded"] },
+                        "reason": { "type": "string" },
+                        "service": { "type": "string" },
+                        "version": { "type": "string" },
+                        "timestamp": { "type": "string", "format": "date-time" }
+                    }
+                },
+                "Stats": {
+                    "type": "object",
+                    "properties": {
+                        "server": { "type": "object" },
+                        "http": { "type": "object" }
+                    }
+                },
+                "Error": {
+                    "type": "object",
+                    "properties": {
+                        "success": { "type": "boolean" },
+                        "error": { "type": "string" }
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::http_stream_server::{build_router, AppState};
+    use crate::mcp::protocol_handler::ProtocolHandler;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        response::Response,
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_paths_follow_prefix_and_admin_token() {
+        let config = HttpServerConfig::default().with_route_prefix("mcp");
+        let doc = openapi_document(&config, &[]);
+        let paths = doc["paths"].as_object().unwrap();
+
+        let expected = [
+            "/health",
+            "/mcp",
+            "/mcp/rpc",
+            "/mcp/tools",
+            "/mcp/tools/call",
+            "/mcp/stats",
+        ];
+        for path in expected {
+            assert!(paths.contains_key(path), "missing {path}");
+        }
+        assert!(!paths.contains_key("/admin/shutdown"));
+
+        let config = HttpServerConfig {
+            admin_token: Some("s3cret".to_string()),
+            ..HttpServerConfig::default()
+        };
+        let doc = openapi_document(&config, &[]);
+        assert!(doc["paths"]["/admin/shutdown"]["post"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_openapi_document_served_when_enabled() {
+        let get = || Request::builder().uri("/openapi.json").body(Body::empty()).unwrap();
+
+        let app = build_router(
+            AppState::new(Arc::new(ProtocolHandler::new())),
+            &HttpServerConfig::default(),
+        );
+        let response = app.oneshot(get()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let config = HttpServerConfig {
+            enable_openapi: true,
+            ..HttpServerConfig::default()
+        };
+        let app = build_router(AppState::new(Arc::new(ProtocolHandler::new())), &config);
+        let response = app.oneshot(get()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let doc = body_json(response).await;
+        assert_eq!(doc["openapi"], "3.0.3");
+        let paths = doc["paths"].as_object().unwrap();
+        let expected = [
+            "/",
+            "/health",
+            "/rpc",
+            "/tools",
+            "/tools/call",
+            "/tools/call_stream",
+            "/stats",
+        ];
+        for path in expected {
+            assert!(paths.contains_key(path), "missing {path}");
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Opaque cursor pagination for list methods
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+/// Encode a list offset as an opaque cursor
+pub fn encode_cursor(offset: usize) -> String {
+    BASE64.encode(format!("offset:{offset}"))
+}
+
+/// Decode a cursor produced by [`encode_cursor`]
+pub fn decode_cursor(cursor: &str) -> Result<usize, String> {
+    BASE64
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|s| s.strip_prefix("offset:").and_then(|n| n.parse().ok()))
+        .ok_or_else(|| format!("Invalid cursor: {cursor}"))
+}
+
+/// Slice one page out of `items`, returning the cursor for the next page
+pub fn paginate<T>(
+    items: Vec<T>,
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Result<(Vec<T>, Option<String>), String> {
+    let offset = cursor.map(decode_cursor).transpose()?.unwrap_or(0);
+    if offset > items.len() {
+        return Err(format!("Invalid cursor: offset {offset} is past the end"));
+    }
+
+    let end = offset.saturating_add(page_size.max(1)).min(items.len());
+    let next_cursor = (end < items.len()).then(|| encode_cursor(end));
+    let page = items.into_iter().skip(offset).take(end - offset).collect();
+    Ok((page, next_cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate_many_items() {
+        let items: Vec<usize> = (0..123).collect();
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        let mut pages = 0;
+
+        loop {
+            let (page, next) = paginate(items.clone(), cursor.as_deref(), 10).unwrap();
+            assert!(page.len() <= 10);
+            seen.extend(page);
+            pages += 1;
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 13);
+        assert_eq!(seen, items);
+    }
+
+    #[test]
+    fn test_paginate_invalid_cursor() {
+        let items: Vec<usize> = (0..5).collect();
+        assert!(paginate(items.clone(), Some("not-a-cursor"), 2).is_err());
+        assert!(paginate(items, Some(&encode_cursor(6)), 2).is_err());
+    }
+}
@@ -4,45 +4,33 @@
 //! Auth and textgen tools will be added in later tasks.
 
 use anyhow::Result;
-use rmcp::model::{
-    InitializeResult, Implementation, ProtocolVersion, ServerCapabilities,
-    Tool,
-};
+use rmcp::model::{Implementation, Tool};
 use serde_json::{json, Value};
 use std::sync::Arc;
-use tracing::{error, info, instrument};
-
-type JsonObject = serde_json::Map<String, Value>;
-
-#[cfg(feature = "postgres")]
-use crate::tools::db;
-
-#[cfg(feature = "auth")]
-use crate::tools::auth;
-
-#[cfg(feature = "auth")]
-use crate::tools::textgen;
-
-#[cfg(feature = "auth")]
-use crate::tools::credits;
-
-#[cfg(feature = "auth")]
-use crate::tools::upload;
-
-use crate::metrics;
+use tracing::{info, instrument};
+
+use crate::mcp::authorization::ToolPermissions;
+use crate::mcp::jsonrpc::{error_response, is_notification, request_method, result_response};
+use crate::mcp::pagination::paginate;
+use crate::mcp::server_info::{
+    negotiate_protocol_version, ServerInfoBuilder, SERVER_INSTRUCTIONS,
+    SUPPORTED_PROTOCOL_VERSIONS,
+};
+use crate::mcp::stats::{ServerStatsProvider, ServerStatsSnapshot, StatsRecorder, UNKNOWN_TOOL};
+use crate::mcp::tool_call::{call_tool, prepare_call, CallContext, ToolCallError};
+use crate::mcp::tool_set::{BuiltinTools, ToolSet};
 
-/// Helper function to convert Value to Arc<JsonObject>
-fn value_to_schema(value: Value) -> Arc<JsonObject> {
-    match value {
-        Value::Object(map) => Arc::new(map),
-        _ => Arc::new(serde_json::Map::new()),
-    }
-}
+/// Default number of tools returned per `tools/list` page
+const DEFAULT_TOOLS_PAGE_SIZE: usize = 50;
 
 /// Protocol handler for HTTP streaming transport
 #[derive(Clone)]
 pub struct ProtocolHandler {
     server_info: ServerInfo,
+    tools_page_size: usize,
+    stats: Arc<StatsRecorder>,
+    tool_permissions: Arc<ToolPermissions>,
+    tools: Arc<dyn ToolSet>,
 }
 
 /// Server information
@@ -66,477 +54,259 @@ impl ProtocolHandler {
     pub fn new() -> Self {
         Self {
             server_info: ServerInfo::default(),
+            tools_page_size: DEFAULT_TOOLS_PAGE_SIZE,
+            stats: Arc::new(StatsRecorder::new()),
+            tool_permissions: Arc::new(ToolPermissions::default()),
+            tools: Arc::new(BuiltinTools),
         }
     }
 
-    /// Handle a JSON-RPC request string and return a JSON-RPC response string
-    #[instrument(skip(self, request_str))]
+    /// Serve `tools` instead of the built-in tools
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn with_tools(mut self, tools: Arc<dyn ToolSet>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Gate tools by caller role; anonymous callers are refused gated tools
+    #[cfg_attr(not(feature = "http-stream"), allow(dead_code))]
+    pub fn with_tool_permissions(mut self, permissions: ToolPermissions) -> Self {
+        self.tool_permissions = Arc::new(permissions);
+        self
+    }
+
+    /// Handle a JSON-RPC request string and return a JSON-RPC response string.
+    ///
+    /// Accepts a single request object or a batch array. A notification, or a
+    /// batch made up only of notifications, yields an empty string (nothing to
+    /// send back).
     pub async fn handle_request(&self, request_str: &str) -> Result<String> {
+        self.handle_request_with(request_str, &CallContext::default()).await
+    }
+
+    /// Same as [`Self::handle_request`] with a transport-supplied context: the
+    /// caller's role is checked against the tool permissions and tool calls
+    /// are cancelled at the deadline.
+    #[instrument(skip(self, request_str, ctx))]
+    pub async fn handle_request_with(
+        &self,
+        request_str: &str,
+        ctx: &CallContext,
+    ) -> Result<String> {
         let request: Value = match serde_json::from_str(request_str) {
             Ok(v) => v,
             Err(e) => {
-                let response = self.error_response(None, -32700, format!("Parse error: {e}"));
+                let response = error_response(None, -32700, format!("Parse error: {e}"));
                 return Ok(response.to_string());
             }
         };
 
-        let id = request.get("id").cloned();
-        let method = request
-            .get("method")
-            .and_then(|m| m.as_str())
-            .unwrap_or("");
-
-        let response = match method {
-            "initialize" => self.handle_initialize(id).await,
-            "initialized" => self.handle_initialized().await,
-            "tools/list" => self.handle_list_tools(id).await,
-            "tools/call" => self.handle_call_tool(id, request).await,
-            "ping" => self.handle_ping(id).await,
-            _ => self.error_response(id, -32601, format!("Method not found: {method}")),
+        let response = match request {
+            Value::Array(batch) => match self.handle_batch(batch, ctx).await {
+                Some(responses) => Value::Array(responses),
+                None => return Ok(String::new()),
+            },
+            request if is_notification(&request) => {
+                self.handle_message(request, ctx).await;
+                return Ok(String::new());
+            }
+            request => self.handle_message(request, ctx).await,
         };
 
         Ok(response.to_string())
     }
 
+    /// Handle a JSON-RPC batch. Notifications (valid requests without an `id`)
+    /// are executed but produce no entry; invalid members always get an
+    /// error entry. `None` means there is nothing to respond with.
+    async fn handle_batch(&self, batch: Vec<Value>, ctx: &CallContext) -> Option<Vec<Value>> {
+        if batch.is_empty() {
+            return Some(vec![error_response(
+                None,
+                -32600,
+                "Invalid Request: empty batch".to_string(),
+            )]);
+        }
+
+        let mut responses = Vec::with_capacity(batch.len());
+        for request in batch {
+            let notification = is_notification(&request);
+            let response = self.handle_message(request, ctx).await;
+            if !notification {
+                responses.push(response);
+            }
+        }
+
+        (!responses.is_empty()).then_some(responses)
+    }
+
+    /// Handle a single JSON-RPC message, recording it in the server stats
+    async fn handle_message(&self, request: Value, ctx: &CallContext) -> Value {
+        let response = self.dispatch_message(request, ctx).await;
+        self.stats.record_request(response.get("error").is_some());
+        response
+    }
+
+    /// Validate a single JSON-RPC 2.0 envelope and dispatch it by method
+    async fn dispatch_message(&self, request: Value, ctx: &CallContext) -> Value {
+        let id = request.get("id").cloned();
+        let method = match request_method(&request) {
+            Ok(method) => method.to_string(),
+            Err(message) => return error_response(id, -32600, message),
+        };
+
+        match method.as_str() {
+            "initialize" => self.handle_initialize(id, &request).await,
+            "initialized" | "notifications/initialized" => self.handle_initialized(id).await,
+            "tools/list" => self.handle_list_tools(id, &request).await,
+            "tools/call" => self.handle_call_tool(id, request, ctx).await,
+            "ping" => result_response(id, json!({})),
+            _ => error_response(id, -32601, format!("Method not found: {method}")),
+        }
+    }
+
     /// Handle initialize request
-    async fn handle_initialize(&self, id: Option<Value>) -> Value {
+    ///
+    /// The response carries the protocol version negotiated from the
+    /// client's `params.protocolVersion`.
+    async fn handle_initialize(&self, id: Option<Value>, request: &Value) -> Value {
         info!("Initialize request received");
 
-        let result = InitializeResult {
-            protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder()
-                .enable_tools()
-                .build(),
-            server_info: Implementation {
+        let requested = request
+            .get("params")
+            .and_then(|p| p.get("protocolVersion"))
+            .and_then(|v| v.as_str());
+
+        let protocol_version = match negotiate_protocol_version(requested) {
+            Ok(version) => version,
+            Err(message) => {
+                let mut response = error_response(id, -32602, message);
+                response["error"]["data"] = json!({
+                    "requested": requested,
+                    "supported": SUPPORTED_PROTOCOL_VERSIONS
+                        .iter()
+                        .map(|v| v.to_string())
+                        .collect::<Vec<_>>()
+                });
+                return response;
+            }
+        };
+        info!("Negotiated protocol version {}", protocol_version);
+
+        let result = ServerInfoBuilder::new()
+            .with_protocol_version(protocol_version)
+            .with_implementation(Implementation {
                 name: self.server_info.name.clone(),
                 version: self.server_info.version.clone(),
                 title: None,
                 icons: None,
                 website_url: None,
-            },
-            instructions: Some(
-                "Đấu Trường Vui MCP Backend. Tools: auth (PostgreSQL auth), db (PostgreSQL via PostgREST), textgen (AI via V5 proxy).".to_string(),
-            ),
-        };
+            })
+            .with_instructions(SERVER_INSTRUCTIONS)
+            .build();
 
-        json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "result": serde_json::to_value(result).unwrap_or(json!({}))
-        })
+        result_response(id, serde_json::to_value(result).unwrap_or(json!({})))
     }
 
     /// Handle initialized notification
-    async fn handle_initialized(&self) -> Value {
+    async fn handle_initialized(&self, id: Option<Value>) -> Value {
         info!("Client initialized");
-        json!({})
+        result_response(id, json!({}))
     }
 
     /// Handle tools/list request
-    async fn handle_list_tools(&self, id: Option<Value>) -> Value {
+    ///
+    /// Cursor-paginated: `params.cursor` selects the page and `nextCursor` is
+    /// set while more tools remain.
+    async fn handle_list_tools(&self, id: Option<Value>, request: &Value) -> Value {
         info!("List tools request");
 
-        let mut tools: Vec<Tool> = Vec::new();
-
-        #[cfg(feature = "auth")]
-        tools.push(Tool {
-            name: "credits".to_string().into(),
-            title: None,
-            description: Some(
-                "Credit management tool for Đấu Trường Vui. Actions: wallet (get/create wallet), deduct (deduct credits for tool usage), claim_welcome_bonus (one-time new user bonus), claim_daily_bonus (daily login bonus). All actions require JWT token.".into()
-            ),
-            input_schema: value_to_schema(json!({
-                "type": "object",
-                "properties": {
-                    "action": {
-                        "type": "string",
-                        "enum": ["wallet", "deduct", "claim_welcome_bonus", "claim_daily_bonus"],
-                        "description": "Credit action to perform"
-                    },
-                    "token": {
-                        "type": "string",
-                        "description": "JWT token (required for all actions)"
-                    },
-                    "tool_id": {
-                        "type": "string",
-                        "description": "Tool ID for deduction (deduct action)"
-                    },
-                    "amount": {
-                        "type": "number",
-                        "description": "Credit amount to deduct (optional, defaults to tool cost from settings)"
-                    }
-                },
-                "required": ["action", "token"]
-            })),
-            output_schema: None,
-            annotations: None,
-            icons: None,
-            meta: None,
-        });
-
-        #[cfg(feature = "auth")]
-        tools.push(Tool {
-            name: "upload".to_string().into(),
-            title: None,
-            description: Some(
-                "File upload tool for Đấu Trường Vui. Proxies uploads to S3 via MCP V5. Action: upload_files. Accepts base64-encoded files. Requires JWT token.".into()
-            ),
-            input_schema: value_to_schema(json!({
-                "type": "object",
-                "properties": {
-                    "action": {
-                        "type": "string",
-                        "enum": ["upload_files"],
-                        "description": "Upload action to perform"
-                    },
-                    "token": {
-                        "type": "string",
-                        "description": "JWT token (required)"
-                    },
-                    "files": {
-                        "type": "array",
-                        "description": "Files to upload: [{ name, content (base64), mimetype }]",
-                        "items": {
-                            "type": "object",
-                            "properties": {
-                                "name": { "type": "string" },
-                                "content": { "type": "string" },
-                                "mimetype": { "type": "string" }
-                            },
-                            "required": ["name", "content", "mimetype"]
-                        }
-                    }
-                },
-                "required": ["action", "token", "files"]
-            })),
-            output_schema: None,
-            annotations: None,
-            icons: None,
-            meta: None,
-        });
-
-        #[cfg(feature = "postgres")]
-        tools.push(Tool {
-            name: "db".to_string().into(),
-            title: None,
-            description: Some(
-                "PostgreSQL database tool via PostgREST. Actions: query, insert, update, delete, upsert, rpc, list_tables, describe. Supports filters (eq, neq, gt, gte, lt, lte, like, ilike, is, in, not, contains, containedBy, overlaps).".into()
-            ),
-            input_schema: value_to_schema(json!({
-                "type": "object",
-                "properties": {
-                    "action": {
-                        "type": "string",
-                        "enum": ["query", "insert", "update", "delete", "upsert", "rpc", "list_tables", "describe"],
-                        "description": "Database action to perform"
-                    },
-                    "table": {
-                        "type": "string",
-                        "description": "Target table name (required for CRUD actions)"
-                    },
-                    "select": {
-                        "description": "Columns to select (string or array)"
-                    },
-                    "filters": {
-                        "type": "object",
-                        "description": "Filter conditions: { \"col\": { \"op\": value } }"
-                    },
-                    "data": {
-                        "description": "Data payload for insert/update/upsert"
-                    },
-                    "order": {
-                        "description": "Order spec: [{ \"column\": \"name\", \"direction\": \"asc\" }]"
-                    },
-                    "limit": {
-                        "type": "number",
-                        "description": "Max rows to return"
-                    },
-                    "offset": {
-                        "type": "number",
-                        "description": "Rows to skip"
-                    },
-                    "options": {
-                        "type": "object",
-                        "description": "Options: count (exact), single (bool), return (minimal/representation)"
-                    },
-                    "function_name": {
-                        "type": "string",
-                        "description": "Function name for rpc action"
-                    },
-                    "params": {
-                        "type": "object",
-                        "description": "Parameters for rpc action"
-                    },
-                    "conflict": {
-                        "type": "string",
-                        "description": "Conflict columns for upsert (comma-separated)"
-                    },
-                    "token": {
-                        "type": "string",
-                        "description": "JWT token for PostgREST authorization"
-                    }
-                },
-                "required": ["action"]
-            })),
-            output_schema: None,
-            annotations: None,
-            icons: None,
-            meta: None,
-        });
-
-        #[cfg(feature = "auth")]
-        tools.push(Tool {
-            name: "auth".to_string().into(),
-            title: None,
-            description: Some(
-                "Authentication tool for Đấu Trường Vui. Actions: login, register, google_auth, get_user_info, check_role. Uses PostgreSQL dtv_users table via PostgREST, bcrypt passwords, JWT tokens.".into()
-            ),
-            input_schema: value_to_schema(json!({
-                "type": "object",
-                "properties": {
-                    "action": {
-                        "type": "string",
-                        "enum": ["login", "register", "google_auth", "get_user_info", "check_role"],
-                        "description": "Auth action to perform"
-                    },
-                    "email": {
-                        "type": "string",
-                        "description": "User email (login, register)"
-                    },
-                    "password": {
-                        "type": "string",
-                        "description": "User password (login, register)"
-                    },
-                    "name": {
-                        "type": "string",
-                        "description": "Display name (register)"
-                    },
-                    "google_token": {
-                        "type": "string",
-                        "description": "Google OAuth ID token (google_auth)"
-                    },
-                    "token": {
-                        "type": "string",
-                        "description": "JWT token (get_user_info, check_role)"
-                    }
-                },
-                "required": ["action"]
-            })),
-            output_schema: None,
-            annotations: None,
-            icons: None,
-            meta: None,
-        });
-
-        #[cfg(feature = "auth")]
-        tools.push(Tool {
-            name: "textgen".to_string().into(),
-            title: None,
-            description: Some(
-                "AI text generation via MCP V5 proxy. Supports JSON mode, structured output (json_schema), vision attachments. Credit-gated per toolId.".into()
-            ),
-            input_schema: value_to_schema(json!({
-                "type": "object",
-                "properties": {
-                    "action": {
-                        "type": "string",
-                        "enum": ["generate"],
-                        "description": "Action to perform"
-                    },
-                    "prompt": {
-                        "type": "string",
-                        "description": "Text prompt (required)"
-                    },
-                    "token": {
-                        "type": "string",
-                        "description": "JWT token"
-                    },
-                    "model_code": {
-                        "type": "string",
-                        "description": "Model code (default: gemini-2.5-pro)"
-                    },
-                    "system_prompt": {
-                        "type": "string",
-                        "description": "System prompt"
-                    },
-                    "max_tokens": {
-                        "type": "number",
-                        "description": "Max output tokens"
-                    },
-                    "temperature": {
-                        "type": "number",
-                        "description": "Temperature (0-2)"
-                    },
-                    "json_mode": {
-                        "type": "boolean",
-                        "description": "Simple JSON mode"
-                    },
-                    "response_format": {
-                        "type": "object",
-                        "description": "Structured output format (json_object or json_schema)"
-                    },
-                    "toolId": {
-                        "type": "string",
-                        "description": "Tool ID for credit deduction"
-                    },
-                    "attachments": {
-                        "type": "array",
-                        "description": "Vision attachments [{type, url, mimeType}]"
-                    },
-                    "save_result": {
-                        "type": "object",
-                        "description": "Auto-save result options {toolId, resultSummary}"
-                    }
-                },
-                "required": ["prompt", "token"]
-            })),
-            output_schema: None,
-            annotations: None,
-            icons: None,
-            meta: None,
-        });
-
-        json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "result": {
-                "tools": tools
-            }
-        })
+        let cursor = request
+            .get("params")
+            .and_then(|p| p.get("cursor"))
+            .and_then(|c| c.as_str());
+
+        let (tools, next_cursor) = match paginate(self.list_tools(), cursor, self.tools_page_size) {
+            Ok(page) => page,
+            Err(e) => return error_response(id, -32602, e),
+        };
+
+        let mut result = json!({ "tools": tools });
+        if let Some(next_cursor) = next_cursor {
+            result["nextCursor"] = json!(next_cursor);
+        }
+
+        result_response(id, result)
     }
 
-    /// Handle tools/call request
-    #[instrument(skip(self, request))]
-    async fn handle_call_tool(&self, id: Option<Value>, request: Value) -> Value {
-        let start_time = std::time::Instant::now();
+    /// Tool definitions available with the enabled features.
+    /// Single source for `tools/list` and the HTTP server info endpoint.
+    pub fn list_tools(&self) -> Vec<Tool> {
+        self.tools.tools()
+    }
+
+    /// Names of the tools returned by [`Self::list_tools`]
+    pub fn tool_names(&self) -> Vec<String> {
+        self.list_tools()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect()
+    }
+
+    /// Authorize and validate a call without running it, for transports that
+    /// execute the tool themselves (e.g. HTTP streaming)
+    #[cfg_attr(not(feature = "http-stream"), allow(dead_code))]
+    pub fn prepare_tool_call(
+        &self,
+        tool_name: &str,
+        arguments: &mut Value,
+        ctx: &CallContext,
+    ) -> Result<(), ToolCallError> {
+        prepare_call(self.tools.as_ref(), &self.tool_permissions, tool_name, arguments, ctx)
+    }
 
+    /// Handle tools/call request
+    #[instrument(skip(self, request, ctx))]
+    async fn handle_call_tool(
+        &self,
+        id: Option<Value>,
+        request: Value,
+        ctx: &CallContext,
+    ) -> Value {
         let params = match request.get("params") {
             Some(p) => p,
-            None => return self.error_response(id, -32602, "Missing params".to_string()),
+            None => return error_response(id, -32602, "Missing params".to_string()),
         };
 
         let tool_name = match params.get("name").and_then(|v| v.as_str()) {
             Some(name) => name,
-            None => return self.error_response(id, -32602, "Missing tool name".to_string()),
+            None => return error_response(id, -32602, "Missing tool name".to_string()),
         };
 
-        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
-
-        info!("Calling tool: {} with args: {:?}", tool_name, arguments);
-
-        let result = match tool_name {
-            #[cfg(feature = "postgres")]
-            "db" => self.execute_db(arguments).await,
-            #[cfg(feature = "auth")]
-            "auth" => self.execute_auth(arguments).await,
-            #[cfg(feature = "auth")]
-            "textgen" => self.execute_textgen(arguments).await,
-            #[cfg(feature = "auth")]
-            "credits" => self.execute_credits(arguments).await,
-            #[cfg(feature = "auth")]
-            "upload" => self.execute_upload(arguments).await,
-            _ => Err(format!("Unknown tool: {tool_name}")),
+        let known = self.tools.tools().iter().any(|t| t.name == tool_name);
+        self.stats.record_tool_call(if known { tool_name } else { UNKNOWN_TOOL });
+
+        let arguments = match params.get("arguments") {
+            Some(Value::Null) | None => json!({}),
+            Some(args) => args.clone(),
         };
 
-        // Record metrics
-        let duration = start_time.elapsed().as_secs_f64();
-        let status = if result.is_ok() { "success" } else { "error" };
-        metrics::record_tool_invocation(tool_name, status, duration);
-
-        match result {
-            Ok(content) => json!({
-                "jsonrpc": "2.0",
-                "id": id,
-                "result": {
-                    "content": content,
-                    "isError": false
+        match call_tool(&self.tools, &self.tool_permissions, tool_name, arguments, ctx).await {
+            Ok(content) => result_response(id, json!({ "content": content, "isError": false })),
+            Err(e) => {
+                let mut response = error_response(id, e.code, e.message);
+                if let Some(data) = e.data {
+                    response["error"]["data"] = data;
                 }
-            }),
-            Err(error) => self.error_response(id, -32603, error),
+                response
+            }
         }
     }
+}
 
-    /// Handle ping request
-    async fn handle_ping(&self, id: Option<Value>) -> Value {
-        json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "result": {}
-        })
-    }
-
-    /// Build a JSON-RPC error response
-    fn error_response(&self, id: Option<Value>, code: i32, message: String) -> Value {
-        error!("Error {}: {}", code, message);
-        json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "error": {
-                "code": code,
-                "message": message
-            }
-        })
-    }
-
-    // ==================== Tool Executors ====================
-
-    #[cfg(feature = "postgres")]
-    async fn execute_db(&self, args: Value) -> Result<Vec<Value>, String> {
-        let req: db::DbRequest = serde_json::from_value(args)
-            .map_err(|e| format!("Invalid db request: {e}"))?;
-        let client = db::get_client();
-        let config = db::get_config();
-        let response = db::execute_db(client, config, &req).await;
-        let text = serde_json::to_string_pretty(&response)
-            .unwrap_or_else(|_| format!("{response:?}"));
-        Ok(vec![json!({
-            "type": "text",
-            "text": text
-        })])
-    }
-
-    #[cfg(feature = "auth")]
-    async fn execute_auth(&self, args: Value) -> Result<Vec<Value>, String> {
-        let response = auth::execute(args).await;
-        let text = serde_json::to_string_pretty(&response)
-            .unwrap_or_else(|_| format!("{response:?}"));
-        Ok(vec![json!({
-            "type": "text",
-            "text": text
-        })])
-    }
-
-    #[cfg(feature = "auth")]
-    async fn execute_textgen(&self, args: Value) -> Result<Vec<Value>, String> {
-        let response = textgen::execute(args).await;
-        let text = serde_json::to_string_pretty(&response)
-            .unwrap_or_else(|_| format!("{response:?}"));
-        Ok(vec![json!({
-            "type": "text",
-            "text": text
-        })])
-    }
-
-    #[cfg(feature = "auth")]
-    async fn execute_credits(&self, args: Value) -> Result<Vec<Value>, String> {
-        let response = credits::execute(args).await;
-        let text = serde_json::to_string_pretty(&response)
-            .unwrap_or_else(|_| format!("{response:?}"));
-        Ok(vec![json!({
-            "type": "text",
-            "text": text
-        })])
-    }
-
-    #[cfg(feature = "auth")]
-    async fn execute_upload(&self, args: Value) -> Result<Vec<Value>, String> {
-        let response = upload::execute(args).await;
-        let text = serde_json::to_string_pretty(&response)
-            .unwrap_or_else(|_| format!("{response:?}"));
-        Ok(vec![json!({
-            "type": "text",
-            "text": text
-        })])
+impl ServerStatsProvider for ProtocolHandler {
+    fn stats_snapshot(&self) -> ServerStatsSnapshot {
+        self.stats.snapshot()
     }
 }
 
@@ -549,6 +319,12 @@ impl Default for ProtocolHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::tool_set::testing::StubTools;
+
+    async fn rpc(handler: &ProtocolHandler, request: &str) -> Value {
+        let response = handler.handle_request(request).await.unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
 
     #[test]
     fn test_protocol_handler_creation() {
@@ -560,8 +336,7 @@ mod tests {
     async fn test_handle_initialize() {
         let handler = ProtocolHandler::new();
         let request = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
-        let response = handler.handle_request(request).await.unwrap();
-        let parsed: Value = serde_json::from_str(&response).unwrap();
+        let parsed = rpc(&handler, request).await;
         assert!(parsed.get("result").is_some());
     }
 
@@ -569,27 +344,71 @@ mod tests {
     async fn test_handle_tools_list() {
         let handler = ProtocolHandler::new();
         let request = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list","params":{}}"#;
-        let response = handler.handle_request(request).await.unwrap();
-        let parsed: Value = serde_json::from_str(&response).unwrap();
+        let parsed = rpc(&handler, request).await;
         let tools = parsed["result"]["tools"].as_array().unwrap();
         // With postgres feature, should have db tool
         assert!(!tools.is_empty() || cfg!(not(feature = "postgres")));
     }
 
+    #[tokio::test]
+    async fn test_tool_names_match_tools_list() {
+        let handler = ProtocolHandler::new();
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list","params":{}}"#;
+        let parsed = rpc(&handler, request).await;
+        let listed: Vec<String> = parsed["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(handler.tool_names(), listed);
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_pagination() {
+        let mut handler = ProtocolHandler::new().with_tools(Arc::new(StubTools));
+        handler.tools_page_size = 1;
+        let all = handler.tool_names();
+
+        let mut seen = Vec::new();
+        let mut params = json!({});
+        loop {
+            let request = json!({
+                "jsonrpc": "2.0", "id": 1, "method": "tools/list", "params": params
+            });
+            let parsed = rpc(&handler, &request.to_string()).await;
+            for tool in parsed["result"]["tools"].as_array().unwrap() {
+                seen.push(tool["name"].as_str().unwrap().to_string());
+            }
+            match parsed["result"]["nextCursor"].as_str() {
+                Some(cursor) => params = json!({ "cursor": cursor }),
+                None => break,
+            }
+        }
+
+        assert_eq!(seen, all);
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_bad_cursor() {
+        let handler = ProtocolHandler::new();
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list","params":{"cursor":"???"}}"#;
+        let parsed = rpc(&handler, request).await;
+        assert_eq!(parsed["error"]["code"], -32602);
+    }
+
     #[tokio::test]
     async fn test_handle_ping() {
         let handler = ProtocolHandler::new();
         let request = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":{}}"#;
-        let response = handler.handle_request(request).await.unwrap();
-        let parsed: Value = serde_json::from_str(&response).unwrap();
+        let parsed = rpc(&handler, request).await;
         assert!(parsed.get("result").is_some());
     }
 
     #[tokio::test]
     async fn test_invalid_json() {
         let handler = ProtocolHandler::new();
-        let response = handler.handle_request("not json").await.unwrap();
-        let parsed: Value = serde_json::from_str(&response).unwrap();
+        let parsed = rpc(&handler, "not json").await;
         assert!(parsed.get("error").is_some());
     }
 
@@ -597,8 +416,76 @@ mod tests {
     async fn test_unknown_method() {
         let handler = ProtocolHandler::new();
         let request = r#"{"jsonrpc":"2.0","id":1,"method":"nonexistent","params":{}}"#;
-        let response = handler.handle_request(request).await.unwrap();
-        let parsed: Value = serde_json::from_str(&response).unwrap();
-        assert!(parsed.get("error").is_some());
+        let parsed = rpc(&handler, request).await;
+        assert_eq!(parsed["error"]["code"], -32601);
+        assert_eq!(parsed["id"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_id_preserved() {
+        let handler = ProtocolHandler::new();
+        let request = r#"{"jsonrpc":"2.0","id":"abc-42","method":"tools/list","params":{}}"#;
+        let parsed = rpc(&handler, request).await;
+        assert_eq!(parsed["id"], "abc-42");
+        assert!(parsed["result"]["tools"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_request() {
+        let handler = ProtocolHandler::new();
+
+        let missing_version = r#"{"id":7,"method":"ping"}"#;
+        let parsed = rpc(&handler, missing_version).await;
+        assert_eq!(parsed["error"]["code"], -32600);
+        assert_eq!(parsed["id"], 7);
+
+        let missing_method = r#"{"jsonrpc":"2.0","id":8}"#;
+        let parsed = rpc(&handler, missing_method).await;
+        assert_eq!(parsed["error"]["code"], -32600);
+
+        let not_object = "42";
+        let parsed = rpc(&handler, not_object).await;
+        assert_eq!(parsed["error"]["code"], -32600);
+    }
+
+    #[tokio::test]
+    async fn test_batch_request() {
+        let handler = ProtocolHandler::new();
+        let request = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"ping"},
+            {"jsonrpc":"2.0","method":"notifications/initialized"},
+            {"jsonrpc":"2.0","id":2,"method":"nonexistent"}
+        ]"#;
+        let parsed = rpc(&handler, request).await;
+        let responses = parsed.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], 1);
+        assert!(responses[0].get("result").is_some());
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(responses[1]["error"]["code"], -32601);
+    }
+
+    #[tokio::test]
+    async fn test_batch_edge_cases() {
+        let handler = ProtocolHandler::new();
+
+        let parsed = rpc(&handler, "[]").await;
+        assert_eq!(parsed[0]["error"]["code"], -32600);
+
+        let only_notifications = r#"[{"jsonrpc":"2.0","method":"notifications/initialized"}]"#;
+        let response = handler.handle_request(only_notifications).await.unwrap();
+        assert!(response.is_empty());
+        let notification = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        assert!(handler.handle_request(notification).await.unwrap().is_empty());
+
+        // Invalid members are answered with id null, never dropped
+        let batch = r#"[{"jsonrpc":"1.0","method":"ping"},{"jsonrpc":"2.0"}]"#;
+        let parsed = rpc(&handler, batch).await;
+        let responses = parsed.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        for response in responses {
+            assert_eq!(response["error"]["code"], -32600);
+            assert!(response["id"].is_null());
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,123 @@
+//! Query-string arguments for `GET /tools/:name`
+//!
+//! Parameters are coerced to the types declared in the tool's input schema;
+//! only primitive (string, number, integer, boolean) properties are supported.
+//! Tokens are never accepted here, since query strings end up in access logs.
+
+use rmcp::model::JsonObject;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Coerce query parameters to the property types declared in `schema`.
+/// Parameters without a declared type are passed as strings.
+pub fn query_arguments(
+    schema: &JsonObject,
+    params: &HashMap<String, String>,
+) -> Result<Value, String> {
+    if params.keys().any(|key| key.eq_ignore_ascii_case("token")) {
+        return Err(
+            "Tokens are not accepted in the query string; send an Authorization header"
+                .to_string(),
+        );
+    }
+
+    let properties = schema.get("properties").and_then(|p| p.as_object());
+    let mut arguments = serde_json::Map::new();
+
+    for (key, raw) in params {
+        let declared = properties
+            .and_then(|p| p.get(key))
+            .and_then(|p| p.get("type"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("string");
+
+        let value = match declared {
+            "string" => Value::String(raw.clone()),
+            "integer" => raw
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| format!("Query parameter '{key}' must be an integer"))?,
+            // Integral values stay integers so tools can read them as u64/i64
+            "number" => raw
+                .parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| raw.parse::<u64>().map(Value::from))
+                .ok()
+                .or_else(|| {
+                    raw.parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map(Value::Number)
+                })
+                .ok_or_else(|| format!("Query parameter '{key}' must be a number"))?,
+            "boolean" => match raw.as_str() {
+                "true" | "1" => Value::Bool(true),
+                "false" | "0" => Value::Bool(false),
+                _ => return Err(format!("Query parameter '{key}' must be true or false")),
+            },
+            other => {
+                return Err(format!(
+                    "Query parameter '{key}' has type '{other}'; use POST /tools/call"
+                ))
+            }
+        };
+        arguments.insert(key.clone(), value);
+    }
+
+    Ok(Value::Object(arguments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_query_arguments_coerced_by_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "message": { "type": "string" },
+                "limit": { "type": "integer" },
+                "ratio": { "type": "number" },
+                "amount": { "type": "number" },
+                "verbose": { "type": "boolean" },
+                "filters": { "type": "object" }
+            }
+        });
+        let schema = schema.as_object().unwrap();
+        let params = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+
+        let arguments = query_arguments(
+            schema,
+            &params(&[
+                ("message", "hi"),
+                ("limit", "5"),
+                ("ratio", "0.5"),
+                ("amount", "5"),
+                ("verbose", "true"),
+                ("extra", "7"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            arguments,
+            json!({
+                "message": "hi",
+                "limit": 5,
+                "ratio": 0.5,
+                "amount": 5,
+                "verbose": true,
+                "extra": "7"
+            })
+        );
+        assert!(arguments["amount"].is_u64());
+
+        assert!(query_arguments(schema, &params(&[("limit", "five")])).is_err());
+        assert!(query_arguments(schema, &params(&[("verbose", "maybe")])).is_err());
+        assert!(query_arguments(schema, &params(&[("filters", "{}")])).is_err());
+        assert!(query_arguments(schema, &params(&[("token", "eyJ...")])).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! Per-client rate limiting for the HTTP server
+//!
+//! A token bucket per client IP: each request takes one token, tokens refill
+//! at `per_second` up to `burst`. Requests with no token left get 429 and a
+//! `Retry-After` header. `/health` is never limited.
+//!
+//! IPv6 clients share a bucket per /64, since one host usually holds the
+//! whole prefix. At most `MAX_TRACKED_CLIENTS` buckets are kept; the least
+//! recently seen client is dropped to make room, which costs it nothing as
+//! its bucket is the closest to full.
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Most clients tracked at once; the least recently seen is evicted beyond it
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// Position in `Buckets::by_age`
+    seq: u64,
+}
+
+#[derive(Default)]
+struct Buckets {
+    by_client: HashMap<IpAddr, Bucket>,
+    /// Clients by last request, oldest first
+    by_age: BTreeMap<u64, IpAddr>,
+    next_seq: u64,
+}
+
+/// Token-bucket rate limiter keyed by client IP
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    max_clients: usize,
+    buckets: Mutex<Buckets>,
+}
+
+/// Bucket key for a client address: IPv6 addresses are cut to their /64,
+/// IPv4-mapped ones are treated as IPv4
+fn client_key(addr: IpAddr) -> IpAddr {
+    match addr {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6(Ipv6Addr::from(u128::from(v6) & !(u128::from(u64::MAX)))),
+        },
+        v4 => v4,
+    }
+}
+
+impl RateLimiter {
+    pub fn new(per_second: u32, burst: u32) -> Self {
+        Self {
+            per_second: f64::from(per_second.max(1)),
+            burst: f64::from(burst.max(1)),
+            max_clients: MAX_TRACKED_CLIENTS,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// Take a token for `client`, or return how long until one is available
+    pub fn check(&self, client: IpAddr) -> Result<(), Duration> {
+        self.check_at(client, Instant::now())
+    }
+
+    fn check_at(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        let client = client_key(client);
+        let mut buckets = self.buckets.lock().unwrap();
+        let Buckets {
+            by_client,
+            by_age,
+            next_seq,
+        } = &mut *buckets;
+        let seq = *next_seq;
+        *next_seq += 1;
+
+        if !by_client.contains_key(&client) {
+            if by_client.len() >= self.max_clients {
+                if let Some((_, oldest)) = by_age.pop_first() {
+                    by_client.remove(&oldest);
+                }
+            }
+            by_client.insert(
+                client,
+                Bucket {
+                    tokens: self.burst,
+                    updated: now,
+                    seq,
+                },
+            );
+        }
+        let bucket = by_client.get_mut(&client).expect("bucket just inserted");
+        by_age.remove(&bucket.seq);
+        by_age.insert(seq, client);
+        bucket.seq = seq;
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second))
+        }
+    }
+}
+
+/// Middleware enforcing the limiter; clients without a known address share
+/// one bucket.
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if request.uri().path() == "/health" {
+        return next.run(request).await;
+    }
+
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    match limiter.check(client) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(json!({
+                    "success": false,
+                    "error": "Too many requests, retry later"
+                })),
+            )
+                .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::http_config::HttpServerConfig;
+    use crate::mcp::http_stream_server::{build_router, AppState};
+    use crate::mcp::protocol_handler::ProtocolHandler;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_bucket_refills() {
+        let limiter = RateLimiter::new(2, 3);
+        let client = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check_at(client, start).is_ok());
+        }
+        let retry_after = limiter.check_at(client, start).unwrap_err();
+        assert_eq!(retry_after, Duration::from_millis(500));
+
+        // Half a second refills one token at 2/s
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.check_at(client, later).is_ok());
+        assert!(limiter.check_at(client, later).is_err());
+    }
+
+    #[test]
+    fn test_clients_are_independent() {
+        let limiter = RateLimiter::new(1, 1);
+        let now = Instant::now();
+        let a = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let b = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+        assert!(limiter.check_at(a, now).is_ok());
+        assert!(limiter.check_at(a, now).is_err());
+        assert!(limiter.check_at(b, now).is_ok());
+    }
+
+    #[test]
+    fn test_ipv6_clients_share_a_prefix() {
+        let limiter = RateLimiter::new(1, 1);
+        let now = Instant::now();
+        let host = |last: u16| IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 1, 0, 0, 0, last));
+
+        assert!(limiter.check_at(host(1), now).is_ok());
+        // Another address in the same /64 draws from the same bucket
+        assert!(limiter.check_at(host(2), now).is_err());
+
+        let other = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 2, 0, 0, 0, 1));
+        assert!(limiter.check_at(other, now).is_ok());
+
+        let mapped = IpAddr::V6(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped());
+        assert_eq!(client_key(mapped), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+    }
+
+    #[test]
+    fn test_tracked_clients_are_capped() {
+        let mut limiter = RateLimiter::new(1, 1);
+        limiter.max_clients = 2;
+        let now = Instant::now();
+        let client = |n: u8| IpAddr::V4(Ipv4Addr::new(10, 0, 0, n));
+
+        assert!(limiter.check_at(client(1), now).is_ok());
+        assert!(limiter.check_at(client(2), now).is_ok());
+        assert!(limiter.check_at(client(2), now).is_err());
+        // A third client evicts the least recently seen one (1), not 2
+        assert!(limiter.check_at(client(3), now).is_ok());
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.by_client.len(), 2);
+        assert_eq!(buckets.by_age.len(), 2);
+        assert!(!buckets.by_client.contains_key(&client(1)));
+        drop(buckets);
+        assert!(limiter.check_at(client(2), now).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_returns_429() {
+        let config = HttpServerConfig {
+            rate_limit_per_second: Some(1),
+            rate_limit_burst: Some(2),
+            ..HttpServerConfig::default()
+        };
+        let app = build_router(AppState::new(Arc::new(ProtocolHandler::new())), &config);
+        let get = |uri: &str| {
+            let mut request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            request
+                .extensions_mut()
+                .insert(axum::extract::ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+            request
+        };
+
+        for _ in 0..2 {
+            let response = app.clone().oneshot(get("/tools")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = app.clone().oneshot(get("/tools")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[axum::http::header::RETRY_AFTER], "1");
+
+        // Health checks are exempt
+        let response = app.oneshot(get("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,100 @@
+//! Readiness reporting for the HTTP `/health` endpoint
+//!
+//! Without a probe, `/health` only reports that the process is alive. With
+//! one, a degraded backing service turns the response into a 503.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+/// Result of a readiness probe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Readiness {
+    Ready,
+    Degraded { reason: String },
+}
+
+/// Lightweight readiness probe for backing services
+#[async_trait::async_trait]
+pub trait ReadinessCheck: Send + Sync {
+    async fn readiness(&self) -> Readiness;
+}
+
+/// Readiness probe that checks PostgREST is reachable
+#[cfg(feature = "postgres")]
+pub struct PostgRestReadiness;
+
+#[cfg(feature = "postgres")]
+#[async_trait::async_trait]
+impl ReadinessCheck for PostgRestReadiness {
+    async fn readiness(&self) -> Readiness {
+        use crate::tools::db;
+        match db::check_reachable(db::get_client(), db::get_config()).await {
+            Ok(()) => Readiness::Ready,
+            Err(reason) => Readiness::Degraded { reason },
+        }
+    }
+}
+
+/// `/health` response for `readiness`: 200 when ready, 503 with the reason
+/// when degraded
+pub fn health_response(readiness: Readiness) -> Response {
+    let (status, body) = match readiness {
+        Readiness::Ready => (
+            StatusCode::OK,
+            json!({
+                "status": "ok",
+                "service": "mcp-dautruongvui-be",
+                "version": env!("CARGO_PKG_VERSION"),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }),
+        ),
+        Readiness::Degraded { reason } => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            json!({
+                "status": "degraded",
+                "reason": reason,
+                "service": "mcp-dautruongvui-be",
+                "version": env!("CARGO_PKG_VERSION"),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            }),
+        ),
+    };
+
+    (status, Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_health_ready() {
+        let response = health_response(Readiness::Ready);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_health_degraded_returns_503() {
+        let response = health_response(Readiness::Degraded {
+            reason: "database unreachable".to_string(),
+        });
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let body = body_json(response).await;
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["reason"], "database unreachable");
+    }
+}
@@ -0,0 +1,95 @@
+//! Request-id propagation and per-request tracing spans
+//!
+//! Every request gets an `x-request-id` (the client's value if supplied,
+//! otherwise a fresh UUID). It is recorded on the request span, so all logs
+//! emitted while handling the request carry it, and echoed in the response.
+
+use axum::{body::Body, http::Request, Router};
+use tower::ServiceBuilder;
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::{DefaultOnFailure, DefaultOnResponse, TraceLayer},
+    LatencyUnit,
+};
+use tracing::{info_span, Level};
+
+/// Header used to correlate a request with its log lines and response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Assign and echo request ids; with `logging`, also open a span per request
+pub fn with_request_id<S>(router: Router<S>, logging: bool) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    if !logging {
+        return router.layer(
+            ServiceBuilder::new()
+                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+                .layer(PropagateRequestIdLayer::x_request_id()),
+        );
+    }
+
+    let trace = TraceLayer::new_for_http()
+        .make_span_with(|request: &Request<Body>| {
+            let request_id = request
+                .headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("-");
+            info_span!(
+                "http_request",
+                request_id = %request_id,
+                method = %request.method(),
+                path = %request.uri().path(),
+            )
+        })
+        .on_response(
+            DefaultOnResponse::new()
+                .level(Level::INFO)
+                .latency_unit(LatencyUnit::Millis),
+        )
+        .on_failure(DefaultOnFailure::new().level(Level::ERROR));
+
+    router.layer(
+        ServiceBuilder::new()
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+            .layer(trace)
+            .layer(PropagateRequestIdLayer::x_request_id()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::get};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        with_request_id(Router::new().route("/health", get(|| async { "ok" })), true)
+    }
+
+    #[tokio::test]
+    async fn test_request_id_echoed() {
+        let request = Request::builder()
+            .uri("/health")
+            .header(REQUEST_ID_HEADER, "req-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-123");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_generated() {
+        let request = Request::builder()
+            .uri("/health")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(request_id).is_ok());
+    }
+}
@@ -0,0 +1,169 @@
+//! Shared `initialize` metadata for the stdio and HTTP servers
+//!
+//! Both transports answer `initialize` with the same instructions and
+//! capabilities; `ServerInfoBuilder` assembles them in one place.
+
+use rmcp::model::{Implementation, ProtocolVersion, ServerCapabilities, ServerInfo};
+
+/// Instructions sent to clients on `initialize`
+pub const SERVER_INSTRUCTIONS: &str = "Đấu Trường Vui MCP Backend. Tools: auth (PostgreSQL auth), db (PostgreSQL via PostgREST), textgen (AI via V5 proxy).";
+
+/// Protocol versions the server speaks, newest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: [ProtocolVersion; 2] =
+    [ProtocolVersion::V_2025_03_26, ProtocolVersion::V_2024_11_05];
+
+/// Pick the protocol version to answer an `initialize` with.
+///
+/// A supported version is echoed back. Any other `YYYY-MM-DD` version, newer
+/// or older, is answered with the newest one we support, leaving the client
+/// to decide whether to continue or disconnect. Only values that are not
+/// dates are rejected. No version means the newest.
+pub fn negotiate_protocol_version(requested: Option<&str>) -> Result<ProtocolVersion, String> {
+    let newest = SUPPORTED_PROTOCOL_VERSIONS[0].clone();
+    let Some(requested) = requested else {
+        return Ok(newest);
+    };
+
+    if let Some(version) = SUPPORTED_PROTOCOL_VERSIONS
+        .iter()
+        .find(|v| v.to_string() == requested)
+    {
+        return Ok(version.clone());
+    }
+
+    let is_date = requested.len() == 10
+        && requested
+            .char_indices()
+            .all(|(i, c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() });
+    if is_date {
+        Ok(newest)
+    } else {
+        Err(format!("Unsupported protocol version: {requested}"))
+    }
+}
+
+/// Builder for the `ServerInfo` returned from `initialize`. Only the tools
+/// capability is advertised, as neither server serves resources, prompts
+/// or logging. The protocol version defaults to the newest one in
+/// [`SUPPORTED_PROTOCOL_VERSIONS`].
+#[derive(Debug, Clone)]
+pub struct ServerInfoBuilder {
+    protocol_version: ProtocolVersion,
+    implementation: Implementation,
+    instructions: Option<String>,
+}
+
+impl ServerInfoBuilder {
+    pub fn new() -> Self {
+        Self {
+            protocol_version: SUPPORTED_PROTOCOL_VERSIONS[0].clone(),
+            implementation: Implementation::from_build_env(),
+            instructions: None,
+        }
+    }
+
+    pub fn with_protocol_version(mut self, version: ProtocolVersion) -> Self {
+        self.protocol_version = version;
+        self
+    }
+
+    pub fn with_implementation(mut self, implementation: Implementation) -> Self {
+        self.implementation = implementation;
+        self
+    }
+
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    pub fn build(self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: self.protocol_version,
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: self.implementation,
+            instructions: self.instructions,
+        }
+    }
+}
+
+impl Default for ServerInfoBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::protocol_handler::ProtocolHandler;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_negotiate_protocol_version() {
+        let newest = SUPPORTED_PROTOCOL_VERSIONS[0].clone();
+        assert_eq!(negotiate_protocol_version(None), Ok(newest.clone()));
+        assert_eq!(
+            negotiate_protocol_version(Some("2024-11-05")),
+            Ok(ProtocolVersion::V_2024_11_05)
+        );
+        assert_eq!(negotiate_protocol_version(Some("2099-01-01")), Ok(newest.clone()));
+        assert_eq!(negotiate_protocol_version(Some("2024-10-07")), Ok(newest));
+        assert!(negotiate_protocol_version(Some("v1")).is_err());
+    }
+
+    #[test]
+    fn test_defaults_enable_tools_only() {
+        let info = ServerInfoBuilder::new().build();
+        assert_eq!(info.protocol_version, SUPPORTED_PROTOCOL_VERSIONS[0]);
+        assert!(info.capabilities.tools.is_some());
+        assert!(info.capabilities.resources.is_none());
+        assert!(info.capabilities.prompts.is_none());
+        assert!(info.capabilities.logging.is_none());
+        assert!(info.instructions.is_none());
+    }
+
+    #[test]
+    fn test_instructions_and_version() {
+        let info = ServerInfoBuilder::new()
+            .with_instructions("Use the db tool for queries")
+            .with_protocol_version(ProtocolVersion::V_2024_11_05)
+            .build();
+
+        assert_eq!(info.instructions.as_deref(), Some("Use the db tool for queries"));
+        assert_eq!(info.protocol_version, ProtocolVersion::V_2024_11_05);
+        assert!(info.capabilities.tools.is_some());
+    }
+
+    async fn initialize(handler: &ProtocolHandler, version: &str) -> Value {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": { "protocolVersion": version }
+        });
+        let response = handler.handle_request(&request.to_string()).await.unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_negotiates_version() {
+        let handler = ProtocolHandler::new();
+        let parsed = initialize(&handler, "2099-01-01").await;
+        assert_eq!(
+            parsed["result"]["protocolVersion"],
+            SUPPORTED_PROTOCOL_VERSIONS[0].to_string()
+        );
+
+        let parsed = initialize(&handler, "2024-10-07").await;
+        assert_eq!(
+            parsed["result"]["protocolVersion"],
+            SUPPORTED_PROTOCOL_VERSIONS[0].to_string()
+        );
+
+        let parsed = initialize(&handler, "latest").await;
+        assert_eq!(parsed["error"]["code"], -32602);
+        assert_eq!(parsed["error"]["data"]["requested"], "latest");
+        assert!(parsed["error"]["data"]["supported"].is_array());
+    }
+}
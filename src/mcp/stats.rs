@@ -0,0 +1,118 @@
+//! Server statistics shared across transports
+//!
+//! `ServerStatsProvider` gives every server the same snapshot shape, so the
+//! HTTP `/stats` endpoint can report it alongside transport-level counters.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Bucket for calls to tools the server does not serve, so client-chosen
+/// names cannot grow `per_tool_counts`
+pub const UNKNOWN_TOOL: &str = "unknown";
+
+/// Point-in-time view of a server's request counters
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ServerStatsSnapshot {
+    pub uptime_seconds: u64,
+    pub total_requests: u64,
+    pub total_errors: u64,
+    pub per_tool_counts: BTreeMap<String, u64>,
+}
+
+/// Implemented by servers that can report a [`ServerStatsSnapshot`]
+pub trait ServerStatsProvider: Send + Sync {
+    fn stats_snapshot(&self) -> ServerStatsSnapshot;
+}
+
+#[derive(Default)]
+struct Counters {
+    total_requests: u64,
+    total_errors: u64,
+    per_tool_counts: BTreeMap<String, u64>,
+}
+
+/// Thread-safe request counters backing a [`ServerStatsProvider`]
+pub struct StatsRecorder {
+    started: Instant,
+    counters: Mutex<Counters>,
+}
+
+impl StatsRecorder {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            counters: Mutex::new(Counters::default()),
+        }
+    }
+
+    /// Count one handled request; `is_error` when it produced an error response
+    pub fn record_request(&self, is_error: bool) {
+        let mut counters = self.counters.lock().unwrap();
+        counters.total_requests += 1;
+        if is_error {
+            counters.total_errors += 1;
+        }
+    }
+
+    /// Count one call of `tool`
+    pub fn record_tool_call(&self, tool: &str) {
+        let mut counters = self.counters.lock().unwrap();
+        *counters.per_tool_counts.entry(tool.to_string()).or_default() += 1;
+    }
+
+    pub fn snapshot(&self) -> ServerStatsSnapshot {
+        let counters = self.counters.lock().unwrap();
+        ServerStatsSnapshot {
+            uptime_seconds: self.started.elapsed().as_secs(),
+            total_requests: counters.total_requests,
+            total_errors: counters.total_errors,
+            per_tool_counts: counters.per_tool_counts.clone(),
+        }
+    }
+}
+
+impl Default for StatsRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder_counts() {
+        let recorder = StatsRecorder::new();
+        recorder.record_request(false);
+        recorder.record_request(true);
+        recorder.record_tool_call("db");
+        recorder.record_tool_call("db");
+        recorder.record_tool_call("auth");
+
+        let snapshot = recorder.snapshot();
+        assert_eq!(snapshot.total_requests, 2);
+        assert_eq!(snapshot.total_errors, 1);
+        assert_eq!(snapshot.per_tool_counts["db"], 2);
+        assert_eq!(snapshot.per_tool_counts["auth"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_handler_stats_snapshot() {
+        use crate::mcp::protocol_handler::ProtocolHandler;
+
+        let handler = ProtocolHandler::new();
+        let ping = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        let unknown = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"nope"}}"#;
+        handler.handle_request(ping).await.unwrap();
+        handler.handle_request(unknown).await.unwrap();
+
+        let snapshot = handler.stats_snapshot();
+        assert_eq!(snapshot.total_requests, 2);
+        assert_eq!(snapshot.total_errors, 1);
+        assert_eq!(snapshot.per_tool_counts[UNKNOWN_TOOL], 1);
+        assert!(!snapshot.per_tool_counts.contains_key("nope"));
+    }
+}
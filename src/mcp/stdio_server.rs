@@ -15,6 +15,8 @@ use rmcp::{
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
+
+use crate::mcp::server_info::{ServerInfoBuilder, SERVER_INSTRUCTIONS};
 use tracing::{info, instrument};
 
 #[derive(Clone)]
@@ -127,7 +129,18 @@ impl McpServer {
         info!("Ready to receive MCP requests");
 
         let service = self.serve(rmcp::transport::stdio()).await?;
+
+        // Ctrl-C stops the service loop and closes the transport cleanly
+        let cancel = service.cancellation_token();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                info!("Shutdown signal received, stopping stdio server");
+                cancel.cancel();
+            }
+        });
+
         service.waiting().await?;
+        info!("Stdio server stopped");
 
         Ok(())
     }
@@ -144,16 +157,11 @@ impl Default for McpServer {
 #[task_handler]
 impl ServerHandler for McpServer {
     fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder()
-                .enable_tools()
-                .build(),
-            server_info: Implementation::from_build_env(),
-            instructions: Some(
-                "Đấu Trường Vui MCP Backend. Tools: auth (PostgreSQL auth), db (PostgreSQL via PostgREST), textgen (AI via V5 proxy).".to_string(),
-            ),
-        }
+        // Stdio does not negotiate; keep the version it has always advertised
+        ServerInfoBuilder::new()
+            .with_protocol_version(ProtocolVersion::V_2024_11_05)
+            .with_instructions(SERVER_INSTRUCTIONS)
+            .build()
     }
 }
 
@@ -166,6 +174,14 @@ mod tests {
         let _server = McpServer::new();
     }
 
+    #[test]
+    fn test_get_info() {
+        let info = McpServer::new().get_info();
+        assert_eq!(info.instructions.as_deref(), Some(SERVER_INSTRUCTIONS));
+        assert!(info.capabilities.tools.is_some());
+        assert_eq!(info.protocol_version, ProtocolVersion::V_2024_11_05);
+    }
+
     #[test]
     fn test_server_default() {
         let _server = McpServer::default();
@@ -0,0 +1,231 @@
+//! The `tools/call` pipeline, independent of JSON-RPC framing
+//!
+//! A call is authorized against the tool permissions, has its schema
+//! defaults filled in and is validated, then runs on an isolated task bounded
+//! by the request deadline. Failures carry the JSON-RPC code and `data` the
+//! protocol handler responds with.
+
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::mcp::authorization::{Principal, ToolPermissions};
+use crate::mcp::tool_schema::{apply_defaults, validate_arguments};
+use crate::mcp::tool_set::ToolSet;
+use crate::mcp::tool_task::run_tool;
+use crate::metrics;
+use crate::utils::redact_json;
+
+/// Per-request context supplied by the transport
+#[derive(Debug, Clone, Default)]
+pub struct CallContext {
+    /// Authenticated caller, checked against the tool permissions
+    pub principal: Option<Principal>,
+    /// Tool execution is cancelled once this passes
+    pub deadline: Option<tokio::time::Instant>,
+}
+
+/// A tool call that did not produce content
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCallError {
+    pub code: i32,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl ToolCallError {
+    fn new(code: i32, message: String) -> Self {
+        Self {
+            code,
+            message,
+            data: None,
+        }
+    }
+
+    fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+/// Authorize the caller for `tool_name`, then fill in schema defaults and
+/// validate `arguments`. Tools `tool_set` does not list are not validated.
+pub fn prepare_call(
+    tool_set: &dyn ToolSet,
+    permissions: &ToolPermissions,
+    tool_name: &str,
+    arguments: &mut Value,
+    ctx: &CallContext,
+) -> Result<(), ToolCallError> {
+    if let Err(denied) = permissions.check(tool_name, ctx.principal.as_ref()) {
+        let caller = ctx.principal.as_ref().map_or("anonymous", |p| p.user_id.as_str());
+        warn!("Denied tool {} for {}: {}", tool_name, caller, denied);
+        return Err(ToolCallError::new(-32003, denied.to_string()).with_data(json!({
+            "kind": "insufficient_permissions",
+            "tool": denied.tool,
+            "required_role": denied.required_role
+        })));
+    }
+
+    if let Some(tool) = tool_set.tools().iter().find(|t| t.name == tool_name) {
+        apply_defaults(&tool.input_schema, arguments);
+        if let Err(violations) = validate_arguments(&tool.input_schema, arguments) {
+            return Err(ToolCallError::new(
+                -32602,
+                format!("Invalid arguments for tool {tool_name}: {}", violations.join("; ")),
+            )
+            .with_data(json!({ "violations": violations })));
+        }
+    }
+    Ok(())
+}
+
+/// Authorize, validate and run one call of `tool_name` from `tool_set`
+pub async fn call_tool(
+    tool_set: &Arc<dyn ToolSet>,
+    permissions: &ToolPermissions,
+    tool_name: &str,
+    mut arguments: Value,
+    ctx: &CallContext,
+) -> Result<Vec<Value>, ToolCallError> {
+    let start_time = std::time::Instant::now();
+
+    prepare_call(tool_set.as_ref(), permissions, tool_name, &mut arguments, ctx)?;
+
+    info!(
+        "Calling tool: {} with args: {}",
+        tool_name,
+        redact_json(&arguments)
+    );
+
+    let name = tool_name.to_string();
+    let tool_set = tool_set.clone();
+    let result = run_tool(tool_name, ctx.deadline, async move {
+        tool_set.execute(&name, arguments).await
+    })
+    .await;
+
+    // Record metrics
+    let duration = start_time.elapsed().as_secs_f64();
+    let status = match &result {
+        Ok(Some(Ok(_))) => "success",
+        Ok(Some(Err(_))) => "error",
+        Ok(None) => "timeout",
+        Err(_) => "panic",
+    };
+    metrics::record_tool_invocation(tool_name, status, duration);
+
+    match result {
+        Ok(Some(Ok(content))) => Ok(content),
+        Ok(Some(Err(error))) => Err(ToolCallError::new(-32603, error)),
+        Ok(None) => Err(ToolCallError::new(
+            -32001,
+            format!("Tool {tool_name} did not finish before the request deadline"),
+        )
+        .with_data(json!({
+            "kind": "deadline_exceeded",
+            "tool": tool_name
+        }))),
+        Err(panic) => Err(ToolCallError::new(-32603, panic.to_string()).with_data(json!({
+            "kind": "tool_panicked",
+            "tool": panic.tool,
+            "message": panic.message
+        }))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::tool_set::testing::StubTools;
+
+    fn principal(role: &str) -> Principal {
+        Principal {
+            user_id: "uuid-1".to_string(),
+            role: role.to_string(),
+        }
+    }
+
+    async fn call_as(
+        permissions: &ToolPermissions,
+        tool: &str,
+        principal: Option<&Principal>,
+    ) -> Result<Vec<Value>, ToolCallError> {
+        let ctx = CallContext {
+            principal: principal.cloned(),
+            ..CallContext::default()
+        };
+        let tool_set: Arc<dyn ToolSet> = Arc::new(StubTools);
+        call_tool(&tool_set, permissions, tool, json!({}), &ctx).await
+    }
+
+    #[tokio::test]
+    async fn test_tool_permissions_allowed() {
+        let permissions = ToolPermissions::default().require("reports", "editor");
+
+        // Passes the permission check and reaches dispatch
+        let err = call_as(&permissions, "reports", Some(&principal("editor")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, -32603);
+        assert!(err.message.contains("Unknown tool"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_permissions_denied() {
+        let permissions = ToolPermissions::default().require("reports", "editor");
+
+        let err = call_as(&permissions, "reports", Some(&principal("user")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, -32003);
+        let data = err.data.unwrap();
+        assert_eq!(data["kind"], "insufficient_permissions");
+        assert_eq!(data["required_role"], "editor");
+    }
+
+    #[tokio::test]
+    async fn test_tool_permissions_deny_anonymous() {
+        let permissions = ToolPermissions::default().require("reports", "editor");
+
+        // A missing, expired or forged token leaves no principal
+        let err = call_as(&permissions, "reports", None).await.unwrap_err();
+        assert_eq!(err.code, -32003);
+
+        // Ungated tools stay open to anonymous callers
+        let err = call_as(&permissions, "other", None).await.unwrap_err();
+        assert_eq!(err.code, -32603);
+    }
+
+    #[tokio::test]
+    async fn test_tool_permissions_admin_override() {
+        let permissions = ToolPermissions::default().require("reports", "editor");
+
+        let err = call_as(&permissions, "reports", Some(&principal("admin")))
+            .await
+            .unwrap_err();
+        assert_ne!(err.code, -32003);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "postgres")]
+    async fn test_call_tool_rejects_invalid_arguments() {
+        let arguments = json!({ "action": "query", "table": "users", "limit": "ten" });
+        let tool_set: Arc<dyn ToolSet> = Arc::new(crate::mcp::tool_set::BuiltinTools);
+        let err = call_tool(
+            &tool_set,
+            &ToolPermissions::default(),
+            "db",
+            arguments,
+            &CallContext::default(),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.code, -32602);
+        assert!(err.data.unwrap()["violations"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("/limit"));
+    }
+}
@@ -0,0 +1,358 @@
+//! HTTP tool-call routes: `/tools/call`, `/tools/:name` and
+//! `/tools/call_stream`
+
+use crate::auth::jwt::Claims;
+use crate::auth::middleware::OptionalAuthToken;
+use crate::mcp::authorization::Principal;
+use crate::mcp::deadline::{tool_call_status, RequestDeadline};
+use crate::mcp::http_stream_server::AppState;
+use crate::mcp::negotiate::negotiated;
+use crate::mcp::query_args::query_arguments;
+use crate::mcp::tool_call::CallContext;
+use crate::mcp::tool_schema::is_read_only_call;
+use axum::{
+    extract::{Extension, Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Context for a tool call made over HTTP. HTTP callers authenticate with a
+/// bearer token, so a request without a valid one is anonymous.
+pub fn call_context(
+    claims: Option<Claims>,
+    deadline: Option<Extension<RequestDeadline>>,
+) -> CallContext {
+    CallContext {
+        principal: claims.map(Principal::from),
+        deadline: deadline.map(|Extension(RequestDeadline(at))| at),
+    }
+}
+
+/// Call tool handler
+pub async fn call_tool_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    OptionalAuthToken(claims): OptionalAuthToken,
+    deadline: Option<Extension<RequestDeadline>>,
+    Json(payload): Json<Value>,
+) -> Response {
+    let tool_name = payload["name"].as_str().unwrap_or("unknown");
+    let ctx = call_context(claims, deadline);
+    let response = dispatch_tool_call(&state, tool_name, &payload["arguments"], &ctx).await;
+    negotiated(&headers, tool_call_status(&response), &response)
+}
+
+/// Query-string tool call handler, e.g. `GET /tools/db?action=query&limit=5`.
+///
+/// Parameters are coerced to the types declared in the tool's input schema;
+/// only primitive (string, number, integer, boolean) properties are supported.
+/// Only calls the schema declares read-only are served; others get 405.
+pub async fn call_tool_query_handler(
+    State(state): State<AppState>,
+    Path(tool_name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    OptionalAuthToken(claims): OptionalAuthToken,
+    deadline: Option<Extension<RequestDeadline>>,
+) -> Response {
+    let Some(tool) = state
+        .protocol_handler
+        .list_tools()
+        .into_iter()
+        .find(|t| t.name == tool_name)
+    else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "success": false,
+                "error": format!("Unknown tool: {tool_name}")
+            })),
+        )
+            .into_response();
+    };
+
+    let arguments = match query_arguments(&tool.input_schema, &params) {
+        Ok(arguments) => arguments,
+        Err(e) => return stream_error(e),
+    };
+    if !is_read_only_call(&tool.input_schema, &arguments) {
+        return (
+            StatusCode::METHOD_NOT_ALLOWED,
+            [(header::ALLOW, "POST")],
+            Json(json!({
+                "success": false,
+                "error": format!("This {tool_name} call may change state; use POST /tools/call")
+            })),
+        )
+            .into_response();
+    }
+
+    let ctx = call_context(claims, deadline);
+    let response = dispatch_tool_call(&state, &tool_name, &arguments, &ctx).await;
+    negotiated(&headers, tool_call_status(&response), &response)
+}
+
+/// Run a `tools/call` through the protocol handler with the caller's context
+async fn dispatch_tool_call(
+    state: &AppState,
+    tool_name: &str,
+    arguments: &Value,
+    ctx: &CallContext,
+) -> Value {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": {
+            "name": tool_name,
+            "arguments": arguments
+        }
+    });
+
+    let request_str = serde_json::to_string(&request).unwrap();
+    let response_str = state
+        .protocol_handler
+        .handle_request_with(&request_str, ctx)
+        .await
+        .unwrap_or_default();
+    serde_json::from_str(&response_str).unwrap_or_else(|_| json!({}))
+}
+
+/// Streaming tool call handler - `db` queries only.
+///
+/// Pages through PostgREST and writes one row per line as
+/// `application/x-ndjson`. `limit`/`offset` in the arguments bound the whole
+/// stream rather than a single page. Calls are authorized and validated like
+/// `/tools/call`; a page that cannot be fetched before the request deadline
+/// ends the stream with an error line.
+pub async fn call_tool_stream_handler(
+    State(state): State<AppState>,
+    OptionalAuthToken(claims): OptionalAuthToken,
+    deadline: Option<Extension<RequestDeadline>>,
+    Json(payload): Json<Value>,
+) -> Response {
+    let tool_name = payload["name"].as_str().unwrap_or("unknown");
+    let ctx = call_context(claims, deadline);
+
+    let mut arguments = match &payload["arguments"] {
+        Value::Null => json!({}),
+        arguments => arguments.clone(),
+    };
+    if let Err(e) = state
+        .protocol_handler
+        .prepare_tool_call(tool_name, &mut arguments, &ctx)
+    {
+        let status = if e.code == -32003 {
+            StatusCode::FORBIDDEN
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        return (
+            status,
+            Json(json!({
+                "success": false,
+                "error": e.message,
+                "code": e.code,
+                "data": e.data
+            })),
+        )
+            .into_response();
+    }
+
+    #[cfg(feature = "postgres")]
+    {
+        use crate::mcp::ndjson::{ndjson_body, paged_rows, DEFAULT_PAGE_SIZE, NDJSON_CONTENT_TYPE};
+        use crate::tools::db;
+
+        if tool_name != "db" {
+            return stream_error(format!("Streaming is not supported for tool: {tool_name}"));
+        }
+
+        let req: db::DbRequest = match serde_json::from_value(arguments) {
+            Ok(req) => req,
+            Err(e) => return stream_error(format!("Invalid db request: {e}")),
+        };
+        if !matches!(req.action.to_lowercase().as_str(), "query" | "select") {
+            return stream_error(format!(
+                "Streaming requires action 'query', got '{}'",
+                req.action
+            ));
+        }
+        if let Err(e) = db::build_request(&req, db::get_config()) {
+            return stream_error(e);
+        }
+
+        tracing::info!("Streaming tool call: db on {:?}", req.table);
+
+        let start_offset = req.offset.unwrap_or(0);
+        let limit = match (req.limit, db::effective_row_cap(&req, db::get_config())) {
+            (Some(limit), Some(cap)) => Some(limit.min(cap)),
+            (limit, cap) => limit.or(cap),
+        };
+        let deadline = ctx.deadline;
+        let fetch = move |offset: u64, page_limit: u64| {
+            let mut page = req.clone();
+            page.offset = Some(offset);
+            page.limit = Some(page_limit);
+            async move {
+                let query = db::execute_db(db::get_client(), db::get_config(), &page);
+                let response = match deadline {
+                    Some(deadline) => tokio::time::timeout_at(deadline, query)
+                        .await
+                        .map_err(|_| "Request deadline exceeded".to_string())?,
+                    None => query.await,
+                };
+                if !response.success {
+                    return Err(response.error.unwrap_or_else(|| "Query failed".to_string()));
+                }
+                Ok(match response.data {
+                    Some(Value::Array(rows)) => rows,
+                    Some(Value::Null) | None => Vec::new(),
+                    Some(row) => vec![row],
+                })
+            }
+        };
+
+        let rows = paged_rows(fetch, start_offset, limit, DEFAULT_PAGE_SIZE);
+        (
+            StatusCode::OK,
+            [(axum::http::header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)],
+            ndjson_body(rows),
+        )
+            .into_response()
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    {
+        stream_error(format!(
+            "Streaming is not supported for tool: {tool_name} (requires the postgres feature)"
+        ))
+    }
+}
+
+/// Error response for requests rejected before dispatch or streaming
+fn stream_error(message: String) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({
+            "success": false,
+            "error": message
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mcp::authorization::ToolPermissions;
+    use crate::mcp::http_config::HttpServerConfig;
+    use crate::mcp::http_stream_server::{build_router, AppState};
+    use crate::mcp::protocol_handler::ProtocolHandler;
+    use crate::mcp::tool_set::testing::StubTools;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        response::Response,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_query_call_unknown_tool_is_404() {
+        let app = build_router(
+            AppState::new(Arc::new(ProtocolHandler::new())),
+            &HttpServerConfig::default(),
+        );
+        let request = Request::builder()
+            .uri("/tools/echo?message=hi")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["error"], "Unknown tool: echo");
+    }
+
+    #[tokio::test]
+    async fn test_stream_route_enforces_tool_roles() {
+        let handler = ProtocolHandler::new()
+            .with_tool_permissions(ToolPermissions::default().require("db", "admin"));
+        let app = build_router(
+            AppState::new(Arc::new(handler)),
+            &HttpServerConfig::default(),
+        );
+        let payload = json!({
+            "name": "db",
+            "arguments": { "action": "query", "table": "users" }
+        });
+        let request = |token: Option<&str>| {
+            let builder = Request::builder()
+                .method("POST")
+                .uri("/tools/call_stream")
+                .header(header::CONTENT_TYPE, "application/json");
+            let builder = match token {
+                Some(token) => builder.header(header::AUTHORIZATION, format!("Bearer {token}")),
+                None => builder,
+            };
+            builder.body(Body::from(payload.to_string())).unwrap()
+        };
+
+        // Anonymous, and a forged token that does not verify
+        for token in [None, Some("forged")] {
+            let response = app.clone().oneshot(request(token)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            let body = body_json(response).await;
+            assert_eq!(body["code"], -32003);
+            assert_eq!(body["data"]["required_role"], "admin");
+        }
+    }
+
+    fn stub_app() -> axum::Router {
+        let handler = ProtocolHandler::new().with_tools(Arc::new(StubTools));
+        build_router(AppState::new(Arc::new(handler)), &HttpServerConfig::default())
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_query_call_keeps_integers() {
+        let response = stub_app().oneshot(get("/tools/echo?limit=5")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // A whole "number" reaches the tool as an integer, not 5.0
+        let arguments = &body_json(response).await["result"]["content"][0];
+        assert_eq!(arguments["limit"], 5);
+        assert!(arguments["limit"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_query_call_read_only_and_tokenless() {
+        let response = stub_app().oneshot(get("/tools/slow?ms=1")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let response = stub_app().oneshot(get("/tools/echo?token=t")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_query_call_rejects_db_writes() {
+        let app = build_router(
+            AppState::new(Arc::new(ProtocolHandler::new())),
+            &HttpServerConfig::default(),
+        );
+        let response = app.oneshot(get("/tools/db?action=delete&table=users")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
@@ -0,0 +1,181 @@
+//! Pre-dispatch handling of tool arguments against the tool's input schema
+//!
+//! Declared `default` values are filled in, then the arguments are validated
+//! so tools only see input that matches their schema.
+
+use serde_json::Value;
+use tracing::error;
+
+type JsonObject = serde_json::Map<String, Value>;
+
+/// Schema keyword that lets a tool opt out of pre-dispatch validation
+pub const RAW_INPUT_KEYWORD: &str = "x-raw-input";
+
+/// Schema keyword marking calls that do not change state: `true` for the
+/// whole tool, or the list of `action` values that only read
+pub const READ_ONLY_KEYWORD: &str = "x-read-only-actions";
+
+/// Whether the schema declares this call read-only (see [`READ_ONLY_KEYWORD`])
+pub fn is_read_only_call(schema: &JsonObject, arguments: &Value) -> bool {
+    match schema.get(READ_ONLY_KEYWORD) {
+        Some(Value::Bool(read_only)) => *read_only,
+        Some(Value::Array(actions)) => arguments
+            .get("action")
+            .is_some_and(|action| actions.contains(action)),
+        _ => false,
+    }
+}
+
+/// Validate tool arguments against the tool's declared input schema.
+///
+/// Returns one message per violation (`/path: reason`). Tools whose schema
+/// sets `"x-raw-input": true` receive their arguments unchecked.
+pub fn validate_arguments(schema: &JsonObject, arguments: &Value) -> Result<(), Vec<String>> {
+    if schema.get(RAW_INPUT_KEYWORD).and_then(|v| v.as_bool()) == Some(true) {
+        return Ok(());
+    }
+
+    let schema = Value::Object(schema.clone());
+    let validator = match jsonschema::validator_for(&schema) {
+        Ok(validator) => validator,
+        Err(e) => {
+            // A broken schema is a server bug; don't block the call on it
+            error!("Invalid tool input schema: {}", e);
+            return Ok(());
+        }
+    };
+
+    let violations: Vec<String> = validator
+        .iter_errors(arguments)
+        .map(|e| {
+            let path = e.instance_path.to_string();
+            if path.is_empty() {
+                e.to_string()
+            } else {
+                format!("{path}: {e}")
+            }
+        })
+        .collect();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// Fill properties missing from `arguments` with the schema's `default`
+/// values, recursing into nested objects. Supplied values are never replaced.
+pub fn apply_defaults(schema: &JsonObject, arguments: &mut Value) {
+    let (Some(properties), Value::Object(arguments)) =
+        (schema.get("properties").and_then(|p| p.as_object()), arguments)
+    else {
+        return;
+    };
+
+    for (name, property) in properties {
+        let Some(property) = property.as_object() else {
+            continue;
+        };
+        match arguments.get_mut(name) {
+            Some(value) => apply_defaults(property, value),
+            None => {
+                if let Some(default) = property.get("default") {
+                    arguments.insert(name.clone(), default.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn prompt_schema() -> JsonObject {
+        match json!({
+            "type": "object",
+            "properties": {
+                "prompt": { "type": "string" }
+            },
+            "required": ["prompt"]
+        }) {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_validate_arguments_ok() {
+        assert!(validate_arguments(&prompt_schema(), &json!({ "prompt": "hi" })).is_ok());
+    }
+
+    #[test]
+    fn test_validate_arguments_reports_violations() {
+        let violations =
+            validate_arguments(&prompt_schema(), &json!({ "prompt": 42 })).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("/prompt"), "{violations:?}");
+
+        let violations = validate_arguments(&prompt_schema(), &json!({})).unwrap_err();
+        assert!(violations[0].contains("prompt"), "{violations:?}");
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let schema = match json!({
+            "type": "object",
+            "properties": {
+                "prompt": { "type": "string" },
+                "style": { "type": "string", "default": "photorealistic" },
+                "size": {
+                    "type": "object",
+                    "properties": {
+                        "width": { "type": "integer", "default": 1024 },
+                        "height": { "type": "integer", "default": 1024 }
+                    }
+                }
+            }
+        }) {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        };
+
+        let mut arguments = json!({ "prompt": "a cat", "size": { "width": 512 } });
+        apply_defaults(&schema, &mut arguments);
+        assert_eq!(
+            arguments,
+            json!({
+                "prompt": "a cat",
+                "style": "photorealistic",
+                "size": { "width": 512, "height": 1024 }
+            })
+        );
+
+        // Supplied values win, even when they fail validation later
+        let mut arguments = json!({ "prompt": "a cat", "style": null });
+        apply_defaults(&schema, &mut arguments);
+        assert_eq!(arguments["style"], Value::Null);
+    }
+
+    #[test]
+    fn test_validate_arguments_raw_input_opt_out() {
+        let mut schema = prompt_schema();
+        schema.insert(RAW_INPUT_KEYWORD.to_string(), json!(true));
+        assert!(validate_arguments(&schema, &json!({ "prompt": 42 })).is_ok());
+    }
+
+    #[test]
+    fn test_is_read_only_call() {
+        let schema = json!({ "type": "object", "x-read-only-actions": ["query", "describe"] });
+        let schema = schema.as_object().unwrap();
+        assert!(is_read_only_call(schema, &json!({ "action": "query" })));
+        assert!(!is_read_only_call(schema, &json!({ "action": "delete" })));
+        assert!(!is_read_only_call(schema, &json!({})));
+
+        let whole_tool = json!({ "type": "object", "x-read-only-actions": true });
+        assert!(is_read_only_call(whole_tool.as_object().unwrap(), &json!({})));
+        assert!(!is_read_only_call(&JsonObject::new(), &json!({})));
+    }
+}
@@ -0,0 +1,483 @@
+//! Built-in tool catalog
+//!
+//! Definitions (name, description, input schema) and dispatch for the tools
+//! compiled in with the enabled features.
+#![cfg_attr(
+    not(any(feature = "postgres", feature = "auth")),
+    allow(unused_imports, unused_mut)
+)]
+
+use rmcp::model::Tool;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+#[cfg(feature = "postgres")]
+use crate::tools::db;
+
+#[cfg(feature = "auth")]
+use crate::tools::{auth, credits, textgen, upload};
+
+type JsonObject = serde_json::Map<String, Value>;
+
+/// Helper function to convert Value to Arc<JsonObject>
+fn value_to_schema(value: Value) -> Arc<JsonObject> {
+    match value {
+        Value::Object(map) => Arc::new(map),
+        _ => Arc::new(serde_json::Map::new()),
+    }
+}
+
+/// A catalog of tools the protocol handler can list and call
+#[async_trait::async_trait]
+pub trait ToolSet: Send + Sync {
+    /// Definitions returned by `tools/list`
+    fn tools(&self) -> Vec<Tool>;
+
+    /// Run `tool_name`; `Err` becomes a -32603 response
+    async fn execute(&self, tool_name: &str, arguments: Value) -> Result<Vec<Value>, String>;
+}
+
+/// The tools compiled in with the enabled features
+pub struct BuiltinTools;
+
+#[async_trait::async_trait]
+impl ToolSet for BuiltinTools {
+    fn tools(&self) -> Vec<Tool> {
+        builtin_tools()
+    }
+
+    async fn execute(&self, tool_name: &str, arguments: Value) -> Result<Vec<Value>, String> {
+        execute_builtin(tool_name, arguments).await
+    }
+}
+
+/// Tool definitions available with the enabled features
+pub fn builtin_tools() -> Vec<Tool> {
+    let mut tools: Vec<Tool> = Vec::new();
+
+    #[cfg(feature = "auth")]
+    tools.push(Tool {
+        name: "credits".to_string().into(),
+        title: None,
+        description: Some(
+            "Credit management tool for Đấu Trường Vui. Actions: wallet (get/create wallet), deduct (deduct credits for tool usage), claim_welcome_bonus (one-time new user bonus), claim_daily_bonus (daily login bonus). All actions require JWT token.".into()
+        ),
+        input_schema: value_to_schema(json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["wallet", "deduct", "claim_welcome_bonus", "claim_daily_bonus"],
+                    "description": "Credit action to perform"
+                },
+                "token": {
+                    "type": "string",
+                    "description": "JWT token (required for all actions)"
+                },
+                "tool_id": {
+                    "type": "string",
+                    "description": "Tool ID for deduction (deduct action)"
+                },
+                "amount": {
+                    "type": "number",
+                    "description": "Credit amount to deduct (optional, defaults to tool cost from settings)"
+                }
+            },
+            "required": ["action", "token"]
+        })),
+        output_schema: None,
+        annotations: None,
+        icons: None,
+        meta: None,
+    });
+
+    #[cfg(feature = "auth")]
+    tools.push(Tool {
+        name: "upload".to_string().into(),
+        title: None,
+        description: Some(
+            "File upload tool for Đấu Trường Vui. Proxies uploads to S3 via MCP V5. Action: upload_files. Accepts base64-encoded files. Requires JWT token.".into()
+        ),
+        input_schema: value_to_schema(json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["upload_files"],
+                    "description": "Upload action to perform"
+                },
+                "token": {
+                    "type": "string",
+                    "description": "JWT token (required)"
+                },
+                "files": {
+                    "type": "array",
+                    "description": "Files to upload: [{ name, content (base64), mimetype }]",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "content": { "type": "string" },
+                            "mimetype": { "type": "string" }
+                        },
+                        "required": ["name", "content", "mimetype"]
+                    }
+                }
+            },
+            "required": ["action", "token", "files"]
+        })),
+        output_schema: None,
+        annotations: None,
+        icons: None,
+        meta: None,
+    });
+
+    #[cfg(feature = "postgres")]
+    tools.push(Tool {
+        name: "db".to_string().into(),
+        title: None,
+        description: Some(
+            "PostgreSQL database tool via PostgREST. Actions: query, insert, update, delete, upsert, rpc, list_tables, describe. Supports filters (eq, neq, gt, gte, lt, lte, like, ilike, is, in, not, contains, containedBy, overlaps).".into()
+        ),
+        input_schema: value_to_schema(json!({
+            "type": "object",
+            "x-read-only-actions": ["query", "list_tables", "describe"],
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["query", "insert", "update", "delete", "upsert", "rpc", "list_tables", "describe"],
+                    "description": "Database action to perform"
+                },
+                "table": {
+                    "type": "string",
+                    "description": "Target table name (required for CRUD actions)"
+                },
+                "select": {
+                    "description": "Columns to select (string or array)"
+                },
+                "filters": {
+                    "type": "object",
+                    "description": "Filter conditions: { \"col\": { \"op\": value } }"
+                },
+                "data": {
+                    "description": "Data payload for insert/update/upsert"
+                },
+                "order": {
+                    "description": "Order spec: [{ \"column\": \"name\", \"direction\": \"asc\" }]"
+                },
+                "limit": {
+                    "type": "number",
+                    "description": "Max rows to return"
+                },
+                "max_rows": {
+                    "type": "number",
+                    "description": "Row cap for query; can only lower the server's DB_MAX_ROWS. Response has truncated=true when hit"
+                },
+                "offset": {
+                    "type": "number",
+                    "description": "Rows to skip"
+                },
+                "options": {
+                    "type": "object",
+                    "description": "Options: count (exact), single (bool), return (minimal/representation)"
+                },
+                "function_name": {
+                    "type": "string",
+                    "description": "Function name for rpc action"
+                },
+                "params": {
+                    "type": "object",
+                    "description": "Parameters for rpc action"
+                },
+                "conflict": {
+                    "type": "string",
+                    "description": "Conflict columns for upsert (comma-separated)"
+                },
+                "token": {
+                    "type": "string",
+                    "description": "JWT token for PostgREST authorization"
+                }
+            },
+            "required": ["action"]
+        })),
+        output_schema: None,
+        annotations: None,
+        icons: None,
+        meta: None,
+    });
+
+    #[cfg(feature = "auth")]
+    tools.push(Tool {
+        name: "auth".to_string().into(),
+        title: None,
+        description: Some(
+            "Authentication tool for Đấu Trường Vui. Actions: login, register, google_auth, get_user_info, check_role. Uses PostgreSQL dtv_users table via PostgREST, bcrypt passwords, JWT tokens.".into()
+        ),
+        input_schema: value_to_schema(json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["login", "register", "google_auth", "get_user_info", "check_role"],
+                    "description": "Auth action to perform"
+                },
+                "email": {
+                    "type": "string",
+                    "description": "User email (login, register)"
+                },
+                "password": {
+                    "type": "string",
+                    "description": "User password (login, register)"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Display name (register)"
+                },
+                "google_token": {
+                    "type": "string",
+                    "description": "Google OAuth ID token (google_auth)"
+                },
+                "token": {
+                    "type": "string",
+                    "description": "JWT token (get_user_info, check_role)"
+                }
+            },
+            "required": ["action"]
+        })),
+        output_schema: None,
+        annotations: None,
+        icons: None,
+        meta: None,
+    });
+
+    #[cfg(feature = "auth")]
+    tools.push(Tool {
+        name: "textgen".to_string().into(),
+        title: None,
+        description: Some(
+            "AI text generation via MCP V5 proxy. Supports JSON mode, structured output (json_schema), vision attachments. Credit-gated per toolId.".into()
+        ),
+        input_schema: value_to_schema(json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["generate"],
+                    "description": "Action to perform"
+                },
+                "prompt": {
+                    "type": "string",
+                    "description": "Text prompt (required)"
+                },
+                "token": {
+                    "type": "string",
+                    "description": "JWT token"
+                },
+                "model_code": {
+                    "type": "string",
+                    "description": "Model code (default: gemini-2.5-pro)"
+                },
+                "system_prompt": {
+                    "type": "string",
+                    "description": "System prompt"
+                },
+                "max_tokens": {
+                    "type": "number",
+                    "description": "Max output tokens"
+                },
+                "temperature": {
+                    "type": "number",
+                    "description": "Temperature (0-2)"
+                },
+                "json_mode": {
+                    "type": "boolean",
+                    "description": "Simple JSON mode"
+                },
+                "response_format": {
+                    "type": "object",
+                    "description": "Structured output format (json_object or json_schema)"
+                },
+                "toolId": {
+                    "type": "string",
+                    "description": "Tool ID for credit deduction"
+                },
+                "attachments": {
+                    "type": "array",
+                    "description": "Vision attachments [{type, url, mimeType}]"
+                },
+                "save_result": {
+                    "type": "object",
+                    "description": "Auto-save result options {toolId, resultSummary}"
+                }
+            },
+            "required": ["prompt", "token"]
+        })),
+        output_schema: None,
+        annotations: None,
+        icons: None,
+        meta: None,
+    });
+
+    tools
+}
+
+/// Dispatch a call to a built-in tool by name
+async fn execute_builtin(tool_name: &str, arguments: Value) -> Result<Vec<Value>, String> {
+    match tool_name {
+        #[cfg(feature = "postgres")]
+        "db" => execute_db(arguments).await,
+        #[cfg(feature = "auth")]
+        "auth" => execute_auth(arguments).await,
+        #[cfg(feature = "auth")]
+        "textgen" => execute_textgen(arguments).await,
+        #[cfg(feature = "auth")]
+        "credits" => execute_credits(arguments).await,
+        #[cfg(feature = "auth")]
+        "upload" => execute_upload(arguments).await,
+        _ => {
+            let _ = arguments;
+            Err(format!("Unknown tool: {tool_name}"))
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+async fn execute_db(args: Value) -> Result<Vec<Value>, String> {
+    let req: db::DbRequest = serde_json::from_value(args)
+        .map_err(|e| format!("Invalid db request: {e}"))?;
+    let client = db::get_client();
+    let config = db::get_config();
+    let response = db::execute_db(client, config, &req).await;
+    let text = serde_json::to_string_pretty(&response)
+        .unwrap_or_else(|_| format!("{response:?}"));
+    Ok(vec![json!({
+        "type": "text",
+        "text": text
+    })])
+}
+
+#[cfg(feature = "auth")]
+async fn execute_auth(args: Value) -> Result<Vec<Value>, String> {
+    let response = auth::execute(args).await;
+    let text = serde_json::to_string_pretty(&response)
+        .unwrap_or_else(|_| format!("{response:?}"));
+    Ok(vec![json!({
+        "type": "text",
+        "text": text
+    })])
+}
+
+#[cfg(feature = "auth")]
+async fn execute_textgen(args: Value) -> Result<Vec<Value>, String> {
+    let response = textgen::execute(args).await;
+    let text = serde_json::to_string_pretty(&response)
+        .unwrap_or_else(|_| format!("{response:?}"));
+    Ok(vec![json!({
+        "type": "text",
+        "text": text
+    })])
+}
+
+#[cfg(feature = "auth")]
+async fn execute_credits(args: Value) -> Result<Vec<Value>, String> {
+    let response = credits::execute(args).await;
+    let text = serde_json::to_string_pretty(&response)
+        .unwrap_or_else(|_| format!("{response:?}"));
+    Ok(vec![json!({
+        "type": "text",
+        "text": text
+    })])
+}
+
+#[cfg(feature = "auth")]
+async fn execute_upload(args: Value) -> Result<Vec<Value>, String> {
+    let response = upload::execute(args).await;
+    let text = serde_json::to_string_pretty(&response)
+        .unwrap_or_else(|_| format!("{response:?}"));
+    Ok(vec![json!({
+        "type": "text",
+        "text": text
+    })])
+}
+
+/// Tool sets for exercising the call path without backing services
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use std::time::Duration;
+
+    /// `echo` (read-only) returns its arguments, `slow` sleeps for `ms`
+    /// milliseconds and `boom` panics
+    pub struct StubTools;
+
+    fn stub(name: &str, schema: Value) -> Tool {
+        Tool {
+            name: name.to_string().into(),
+            title: None,
+            description: Some(format!("Test tool {name}").into()),
+            input_schema: value_to_schema(schema),
+            output_schema: None,
+            annotations: None,
+            icons: None,
+            meta: None,
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ToolSet for StubTools {
+        fn tools(&self) -> Vec<Tool> {
+            let echo = json!({
+                "type": "object",
+                "x-read-only-actions": true,
+                "properties": { "limit": { "type": "number" } }
+            });
+            let object = json!({ "type": "object" });
+            vec![stub("echo", echo), stub("slow", object.clone()), stub("boom", object)]
+        }
+
+        async fn execute(&self, tool_name: &str, arguments: Value) -> Result<Vec<Value>, String> {
+            match tool_name {
+                "echo" => Ok(vec![arguments]),
+                "slow" => {
+                    let ms = arguments["ms"].as_u64().unwrap_or(1_000);
+                    tokio::time::sleep(Duration::from_millis(ms)).await;
+                    Ok(vec![json!({ "type": "text", "text": "done" })])
+                }
+                "boom" => panic!("tool exploded"),
+                _ => Err(format!("Unknown tool: {tool_name}")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::StubTools;
+    use super::*;
+    use crate::mcp::protocol_handler::ProtocolHandler;
+
+    #[tokio::test]
+    async fn test_handler_survives_tool_panic() {
+        let handler = ProtocolHandler::new().with_tools(Arc::new(StubTools));
+        let call = |name: &str| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": name, "arguments": { "n": 1 } }
+            })
+            .to_string()
+        };
+
+        let response = handler.handle_request(&call("boom")).await.unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["error"]["code"], -32603);
+        assert_eq!(response["error"]["data"]["kind"], "tool_panicked");
+        assert_eq!(response["error"]["data"]["message"], "tool exploded");
+
+        // The same handler keeps serving calls after the panic
+        let response = handler.handle_request(&call("echo")).await.unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["result"]["content"][0]["n"], 1);
+    }
+}
@@ -0,0 +1,139 @@
+//! Isolated execution of a single tool call
+//!
+//! Each call runs on its own task so a panic becomes an error response, and
+//! is cut off at the request deadline when one is set. The task is aborted
+//! if the caller stops waiting for it (client disconnect, HTTP timeout).
+
+/// A tool panicked while handling a call
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Tool {tool} panicked: {message}")]
+pub struct ToolPanicked {
+    pub tool: String,
+    pub message: String,
+}
+
+/// Aborts the spawned tool task when dropped, so the tool stops with the
+/// request that started it
+struct AbortOnDrop<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Run a tool future on its own task so a panic becomes an error response
+/// instead of unwinding through the request handler. Dropping the returned
+/// future aborts the task.
+pub async fn catch_tool_panic<F, T>(tool: &str, future: F) -> Result<T, ToolPanicked>
+where
+    F: std::future::Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let mut task = AbortOnDrop(tokio::spawn(future));
+    (&mut task.0).await.map_err(|e| {
+        let message = if e.is_panic() {
+            let payload = e.into_panic();
+            payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic payload".to_string())
+        } else {
+            "tool task was cancelled".to_string()
+        };
+        ToolPanicked {
+            tool: tool.to_string(),
+            message,
+        }
+    })
+}
+
+/// Run a tool through [`catch_tool_panic`], dropping its future once
+/// `deadline` passes so the work stops. `Ok(None)` means the deadline hit.
+pub async fn run_tool<F, T>(
+    tool: &str,
+    deadline: Option<tokio::time::Instant>,
+    future: F,
+) -> Result<Option<T>, ToolPanicked>
+where
+    F: std::future::Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    catch_tool_panic(tool, async move {
+        match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, future).await.ok(),
+            None => Some(future.await),
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_catch_tool_panic() {
+        let result = catch_tool_panic("boom", async {
+            panic!("exploded");
+        })
+        .await;
+        let err: ToolPanicked = result.map(|()| ()).unwrap_err();
+        assert_eq!(err.tool, "boom");
+        assert_eq!(err.message, "exploded");
+
+        let result = catch_tool_panic("boom", async {
+            panic!("{}", String::from("formatted panic"));
+        })
+        .await;
+        assert_eq!(result.map(|()| ()).unwrap_err().message, "formatted panic");
+
+        let ok = catch_tool_panic("fine", async { 42 }).await;
+        assert_eq!(ok, Ok(42));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_caller_aborts_tool() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = finished.clone();
+        let caller = catch_tool_panic("slow", async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            flag.store(true, Ordering::SeqCst);
+        });
+
+        // The caller gives up, as when a client disconnects mid-request
+        let gave_up = tokio::time::timeout(std::time::Duration::from_millis(10), caller).await;
+        assert!(gave_up.is_err());
+
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_run_tool_cancels_at_deadline() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = finished.clone();
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(20);
+
+        let result = run_tool("slow", Some(deadline), async move {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            flag.store(true, Ordering::SeqCst);
+        })
+        .await;
+        assert_eq!(result, Ok(None));
+
+        // The tool future was dropped, not left running in the background
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        let fast = run_tool("fast", Some(deadline), async { 7 }).await;
+        assert_eq!(fast, Ok(Some(7)));
+    }
+}
@@ -11,6 +11,8 @@ use serde_json::Value;
 use std::collections::HashSet;
 use std::time::Instant;
 
+use crate::utils::redact_secret;
+
 // ---------------------------------------------------------------------------
 // Configuration
 // ---------------------------------------------------------------------------
@@ -22,10 +24,14 @@ pub struct PostgRestConfig {
     pub timeout_secs: u64,
     pub allowed_tables: Option<HashSet<String>>,
     pub table_prefix: Option<String>,
+    /// Hard cap on rows returned by a query (`DB_MAX_ROWS`)
+    pub max_rows: Option<u64>,
 }
 
 impl PostgRestConfig {
-    pub fn from_env() -> Self {
+    /// Build from environment variables; a `DB_MAX_ROWS` that is not a row
+    /// count is an error rather than silently leaving queries uncapped.
+    pub fn from_env() -> Result<Self, String> {
         let base_url = std::env::var("POSTGREST_URL")
             .unwrap_or_else(|_| "http://localhost:3000".to_string())
             .trim_end_matches('/')
@@ -49,13 +55,19 @@ impl PostgRestConfig {
             .ok()
             .filter(|p| !p.is_empty());
 
-        Self {
+        let max_rows = match std::env::var("DB_MAX_ROWS") {
+            Ok(value) => parse_max_rows(&value)?,
+            Err(_) => None,
+        };
+
+        Ok(Self {
             base_url,
             anon_key,
             timeout_secs,
             allowed_tables,
             table_prefix,
-        }
+            max_rows,
+        })
     }
 
     /// Check if a table is allowed by whitelist and/or prefix.
@@ -86,6 +98,18 @@ impl PostgRestConfig {
     }
 }
 
+/// Parse a `DB_MAX_ROWS` value. Empty or `0` means no cap.
+fn parse_max_rows(value: &str) -> Result<Option<u64>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse::<u64>()
+        .map(|n| Some(n).filter(|&n| n > 0))
+        .map_err(|_| format!("Invalid DB_MAX_ROWS '{value}': expected a row count"))
+}
+
 /// Validate table name to prevent path traversal / injection.
 /// Only allows `[a-zA-Z_][a-zA-Z0-9_]*`.
 pub fn validate_table_name(name: &str) -> Result<(), String> {
@@ -146,6 +170,10 @@ pub struct DbRequest {
     #[serde(default)]
     pub limit: Option<u64>,
 
+    /// Per-call row cap for query; can only lower the configured `DB_MAX_ROWS`
+    #[serde(default, alias = "maxRows")]
+    pub max_rows: Option<u64>,
+
     /// Number of rows to skip
     #[serde(default)]
     pub offset: Option<u64>,
@@ -203,6 +231,10 @@ pub struct DbResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub count: Option<i64>,
 
+    /// Set when a query hit the row cap and more rows were available
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+
     pub metadata: DbMetadata,
 }
 
@@ -235,6 +267,7 @@ impl DbResponse {
             data,
             error: None,
             count,
+            truncated: false,
             metadata: DbMetadata {
                 execution_time_ms: start.elapsed().as_millis() as u64,
                 timestamp: Utc::now().to_rfc3339(),
@@ -251,6 +284,7 @@ impl DbResponse {
             data: None,
             error: Some(msg.into()),
             count: None,
+            truncated: false,
             metadata: DbMetadata {
                 execution_time_ms: start.elapsed().as_millis() as u64,
                 timestamp: Utc::now().to_rfc3339(),
@@ -575,8 +609,16 @@ fn build_query_request(
     if let Some(ref order) = req.order {
         qp.push(("order".to_string(), translate_order(order)?));
     }
-    if let Some(limit) = req.limit {
-        qp.push(("limit".to_string(), limit.to_string()));
+    match effective_row_cap(req, config) {
+        // Fetch one row past the cap so truncation can be detected
+        Some(cap) if req.limit.is_none_or(|limit| limit > cap) => {
+            qp.push(("limit".to_string(), (cap + 1).to_string()));
+        }
+        _ => {
+            if let Some(limit) = req.limit {
+                qp.push(("limit".to_string(), limit.to_string()));
+            }
+        }
     }
     if let Some(offset) = req.offset {
         qp.push(("offset".to_string(), offset.to_string()));
@@ -599,6 +641,26 @@ fn build_query_request(
     })
 }
 
+/// Row cap for a query: the configured `DB_MAX_ROWS`, lowered by a per-call
+/// `max_rows` when given. `None` means unbounded.
+pub fn effective_row_cap(req: &DbRequest, config: &PostgRestConfig) -> Option<u64> {
+    match (config.max_rows, req.max_rows.filter(|&n| n > 0)) {
+        (Some(global), Some(per_call)) => Some(global.min(per_call)),
+        (global, per_call) => global.or(per_call),
+    }
+}
+
+/// Trim query results to `cap` rows, flagging the response as truncated
+fn apply_row_cap(response: &mut DbResponse, cap: u64) {
+    if let Some(Value::Array(rows)) = response.data.as_mut() {
+        if rows.len() as u64 > cap {
+            rows.truncate(cap as usize);
+            response.truncated = true;
+            response.metadata.affected_rows = Some(rows.len());
+        }
+    }
+}
+
 fn build_insert_request(
     req: &DbRequest,
     config: &PostgRestConfig,
@@ -796,12 +858,14 @@ pub async fn normalize_response(
     let response = match result {
         Ok(r) => r,
         Err(e) => {
+            // reqwest errors include the URL, which may carry credentials
+            let detail = redact_secret(&e.to_string());
             let msg = if e.is_timeout() {
-                format!("PostgREST request timed out: {e}")
+                format!("PostgREST request timed out: {detail}")
             } else if e.is_connect() {
-                format!("PostgREST connection failed: {e}")
+                format!("PostgREST connection failed: {detail}")
             } else {
-                format!("PostgREST request error: {e}")
+                format!("PostgREST request error: {detail}")
             };
             return DbResponse::err(msg, action, table, start);
         }
@@ -923,6 +987,12 @@ pub async fn execute_db(
 
     let mut response = normalize_response(result, &action, table, start).await;
 
+    if matches!(action.as_str(), "query" | "select") && response.success {
+        if let Some(cap) = effective_row_cap(req, config) {
+            apply_row_cap(&mut response, cap);
+        }
+    }
+
     // Post-process: for "describe", extract the table definition from the
     // OpenAPI spec returned by the root endpoint.
     if action == "describe" {
@@ -966,9 +1036,32 @@ pub fn get_client() -> &'static Client {
     })
 }
 
-/// Get or initialize the shared PostgREST config
+/// Check that PostgREST answers HTTP requests at all.
+/// Any HTTP status counts as reachable; only transport errors fail.
+pub async fn check_reachable(client: &Client, config: &PostgRestConfig) -> Result<(), String> {
+    client
+        .get(format!("{}/", config.base_url))
+        .timeout(std::time::Duration::from_secs(2))
+        .send()
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("PostgREST unreachable: {}", redact_secret(&e.to_string())))
+}
+
+/// Load the shared PostgREST config at startup so an invalid value stops
+/// the server. `max_rows` (from `--db-max-rows`) overrides `DB_MAX_ROWS`.
+pub fn init_config(max_rows: Option<u64>) -> Result<&'static PostgRestConfig, String> {
+    let mut config = PostgRestConfig::from_env()?;
+    if max_rows.is_some() {
+        config.max_rows = max_rows.filter(|&n| n > 0);
+    }
+    Ok(DB_CONFIG.get_or_init(|| config))
+}
+
+/// Get the shared PostgREST config, loading it from the environment if
+/// [`init_config`] has not run
 pub fn get_config() -> &'static PostgRestConfig {
-    DB_CONFIG.get_or_init(PostgRestConfig::from_env)
+    DB_CONFIG.get_or_init(|| PostgRestConfig::from_env().unwrap_or_else(|e| panic!("{e}")))
 }
 
 // ---------------------------------------------------------------------------
@@ -989,13 +1082,25 @@ mod tests {
         std::env::remove_var("POSTGREST_TIMEOUT");
         std::env::remove_var("DB_ALLOWED_TABLES");
         std::env::remove_var("DB_TABLE_PREFIX");
+        std::env::remove_var("DB_MAX_ROWS");
 
-        let config = PostgRestConfig::from_env();
+        let config = PostgRestConfig::from_env().unwrap();
         assert_eq!(config.base_url, "http://localhost:3000");
         assert!(config.anon_key.is_none());
         assert_eq!(config.timeout_secs, 30);
         assert!(config.allowed_tables.is_none());
         assert!(config.table_prefix.is_none());
+        assert!(config.max_rows.is_none());
+    }
+
+    #[test]
+    fn test_parse_max_rows() {
+        assert_eq!(parse_max_rows("500"), Ok(Some(500)));
+        assert_eq!(parse_max_rows(" 10 "), Ok(Some(10)));
+        assert_eq!(parse_max_rows("0"), Ok(None));
+        assert_eq!(parse_max_rows(""), Ok(None));
+        assert!(parse_max_rows("many").unwrap_err().contains("DB_MAX_ROWS"));
+        assert!(parse_max_rows("-1").is_err());
     }
 
     #[test]
@@ -1006,6 +1111,7 @@ mod tests {
             timeout_secs: 30,
             allowed_tables: None,
             table_prefix: None,
+            max_rows: None,
         };
         assert!(config.is_table_allowed("anything"));
         assert!(config.is_table_allowed("users"));
@@ -1019,6 +1125,7 @@ mod tests {
             timeout_secs: 30,
             allowed_tables: Some(["users", "posts"].iter().map(|s| s.to_string()).collect()),
             table_prefix: None,
+            max_rows: None,
         };
         assert!(config.is_table_allowed("users"));
         assert!(config.is_table_allowed("posts"));
@@ -1033,6 +1140,7 @@ mod tests {
             timeout_secs: 30,
             allowed_tables: None,
             table_prefix: Some("bdtv_".to_string()),
+            max_rows: None,
         };
         assert!(config.is_table_allowed("bdtv_users"));
         assert!(config.is_table_allowed("bdtv_credit_wallets"));
@@ -1047,6 +1155,7 @@ mod tests {
             timeout_secs: 30,
             allowed_tables: Some(["extra_table"].iter().map(|s| s.to_string()).collect()),
             table_prefix: Some("app_".to_string()),
+            max_rows: None,
         };
         assert!(config.is_table_allowed("app_users")); // prefix match
         assert!(config.is_table_allowed("extra_table")); // whitelist match
@@ -1278,6 +1387,7 @@ mod tests {
                     .collect(),
             ),
             table_prefix: None,
+            max_rows: None,
         }
     }
 
@@ -1305,6 +1415,76 @@ mod tests {
         assert!(pg.body.is_none());
     }
 
+    #[test]
+    fn test_build_query_row_cap() {
+        let mut config = test_config();
+        config.max_rows = Some(5);
+        let limit_param = |req: serde_json::Value| {
+            let req = serde_json::from_value::<DbRequest>(req).unwrap();
+            let pg = build_request(&req, &config).unwrap();
+            pg.query_params
+                .into_iter()
+                .find(|(k, _)| k == "limit")
+                .map(|(_, v)| v)
+        };
+
+        // No limit or a limit above the cap probes one extra row
+        assert_eq!(
+            limit_param(serde_json::json!({ "action": "query", "table": "users" })),
+            Some("6".to_string())
+        );
+        assert_eq!(
+            limit_param(serde_json::json!({ "action": "query", "table": "users", "limit": 50 })),
+            Some("6".to_string())
+        );
+        // A limit within the cap is passed through
+        assert_eq!(
+            limit_param(serde_json::json!({ "action": "query", "table": "users", "limit": 3 })),
+            Some("3".to_string())
+        );
+        // Per-call max_rows can lower the cap but not raise it
+        assert_eq!(
+            limit_param(serde_json::json!({ "action": "query", "table": "users", "max_rows": 2 })),
+            Some("3".to_string())
+        );
+        assert_eq!(
+            limit_param(serde_json::json!({ "action": "query", "table": "users", "max_rows": 99 })),
+            Some("6".to_string())
+        );
+    }
+
+    #[test]
+    fn test_apply_row_cap() {
+        let rows: Vec<Value> = (0..6).map(|i| serde_json::json!({ "id": i })).collect();
+        let mut response = DbResponse::ok(
+            Some(Value::Array(rows)),
+            None,
+            Some(6),
+            "query",
+            Some("users"),
+            Instant::now(),
+        );
+        apply_row_cap(&mut response, 5);
+        assert!(response.truncated);
+        assert_eq!(response.data.as_ref().unwrap().as_array().unwrap().len(), 5);
+        assert_eq!(response.metadata.affected_rows, Some(5));
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["truncated"], true);
+
+        let mut small = DbResponse::ok(
+            Some(serde_json::json!([{ "id": 1 }])),
+            None,
+            Some(1),
+            "query",
+            Some("users"),
+            Instant::now(),
+        );
+        apply_row_cap(&mut small, 5);
+        assert!(!small.truncated);
+        assert!(serde_json::to_value(&small).unwrap().get("truncated").is_none());
+    }
+
     #[test]
     fn test_build_insert() {
         let config = test_config();
@@ -1516,6 +1696,7 @@ mod tests {
             timeout_secs: 30,
             allowed_tables: None,
             table_prefix: None,
+            max_rows: None,
         };
         let req = serde_json::from_value::<DbRequest>(serde_json::json!({
             "action": "query",
@@ -1,15 +1,24 @@
-use tracing::{debug, error, info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{debug, error, info, warn, Subscriber};
+use tracing_subscriber::{
+    fmt::MakeWriter, layer::SubscriberExt, registry::LookupSpan, util::SubscriberInitExt, Layer,
+};
+
+/// Log line format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per line; the log text is in the `message` field
+    Json,
+}
 
 pub struct Logger;
 
 impl Logger {
-    pub fn init() {
-        // Create the fmt layer (logging to stderr to avoid interfering with JSON stdout)
-        let fmt_layer = tracing_subscriber::fmt::layer()
-            .with_ansi(false)
-            .with_target(false)
-            .with_writer(std::io::stderr);
+    pub fn init(format: LogFormat) {
+        // Log to stderr to avoid interfering with JSON stdout
+        let fmt_layer = fmt_layer(format, std::io::stderr);
 
         // Create the env filter
         let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
@@ -44,4 +53,66 @@ impl Logger {
     pub fn shutdown() {
         // No-op without OpenTelemetry
     }
-}
\ No newline at end of file
+}
+
+/// Build the fmt layer for `format`, writing to `writer`
+fn fmt_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_ansi(false)
+        .with_target(false)
+        .with_writer(writer);
+
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().boxed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_format_emits_parseable_lines() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber =
+            tracing_subscriber::registry().with(fmt_layer(LogFormat::Json, move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!("🚀 Server started on {}", "127.0.0.1:8030");
+            warn!(tool = "db", "Slow query");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["fields"]["message"], "🚀 Server started on 127.0.0.1:8030");
+        assert_eq!(lines[1]["fields"]["tool"], "db");
+    }
+}
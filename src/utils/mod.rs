@@ -1,4 +1,6 @@
 pub mod config;
 pub mod logger;
+pub mod redact;
 
-pub use logger::Logger;
\ No newline at end of file
+pub use logger::{LogFormat, Logger};
+pub use redact::{redact_json, redact_secret};
\ No newline at end of file